@@ -0,0 +1,107 @@
+use super::*;
+
+use std::sync::mpsc;
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn delegate() -> anyhow::Result<()> {
+    let api = OpdsApi::try_from(DATABASE.as_str())?;
+    let async_api = AsyncOpdsApi::open(&DATABASE, 2)?;
+
+    assert_eq!(
+        async_api.authors_by_genre_id(24).await?,
+        api.authors_by_genre_id(24)?
+    );
+    let name = String::from("Велес");
+    assert_eq!(
+        async_api.authors_by_last_name(name.clone()).await?,
+        api.authors_by_last_name(&name)?
+    );
+    assert_eq!(async_api.book_by_id(768866).await?, api.book_by_id(768866)?);
+
+    let english = async_api.clone().with_languages(vec![String::from("en")]);
+    let books = english
+        .books_by_genre_id_and_date(41, String::from("%"))
+        .await?;
+    assert_eq!(
+        books.iter().map(|book| book.id).collect::<Vec<_>>(),
+        vec![768866, 768876]
+    );
+    // other handles keep all languages
+    let books = async_api
+        .books_by_genre_id_and_date(41, String::from("%"))
+        .await?;
+    assert_eq!(books.len(), 7);
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn parallel() -> anyhow::Result<()> {
+    let api = AsyncOpdsApi::open(&DATABASE, 2)?;
+    let expected = api.series_by_genre_id(24).await?;
+
+    let tasks = (0..8)
+        .map(|_| {
+            let api = api.clone();
+            tokio::spawn(async move { api.series_by_genre_id(24).await })
+        })
+        .collect::<Vec<_>>();
+    for task in tasks {
+        assert_eq!(task.await??, expected);
+    }
+    assert_eq!(api.pending(), 0);
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn busy() -> anyhow::Result<()> {
+    let api = AsyncOpdsApi::open(&DATABASE, 1)?.with_queue_limit(1);
+    let (started, wait_started) = mpsc::channel();
+    let (finish, wait_finish) = mpsc::channel::<()>();
+
+    let running = {
+        let api = api.clone();
+        tokio::spawn(async move {
+            api.call(move |api| {
+                started.send(()).ok();
+                wait_finish.recv().ok();
+                api.latest_date()
+            })
+            .await
+        })
+    };
+    wait_started.recv()?;
+    assert_eq!(api.pending(), 1);
+    assert!(matches!(api.latest_date().await, Err(Error::Busy)));
+
+    finish.send(())?;
+    assert!(running.await??.is_some());
+    assert!(api.latest_date().await?.is_some());
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn cancel() -> anyhow::Result<()> {
+    let api = AsyncOpdsApi::open(&DATABASE, 1)?;
+    let (started, wait_started) = mpsc::channel();
+
+    let endless = {
+        let api = api.clone();
+        tokio::spawn(async move {
+            api.call(move |api| {
+                started.send(()).ok();
+                let sql = "WITH RECURSIVE n(x) AS (SELECT 1 UNION ALL SELECT x + 1 FROM n) \
+                           SELECT count(*) FROM n";
+                Ok(api.conn.query_row(sql, [], |row| row.get::<_, u64>(0))?)
+            })
+            .await
+        })
+    };
+    wait_started.recv()?;
+    endless.abort();
+    assert!(endless.await.is_err_and(|e| e.is_cancelled()));
+
+    // The only connection is released once the statement is interrupted
+    assert!(api.latest_date().await?.is_some());
+    assert_eq!(api.pending(), 0);
+    Ok(())
+}
@@ -2,7 +2,7 @@ use super::*;
 
 #[test]
 fn authors_next_char_by_prefix() -> anyhow::Result<()> {
-    let api = OpdsApi::try_from(DATABASE)?;
+    let api = OpdsApi::try_from(DATABASE.as_str())?;
     let result = api.authors_next_char_by_prefix(&String::from("сТо"))?;
 
     assert_eq!(result, vec!["Стое", "Стоу"]);
@@ -11,7 +11,7 @@ fn authors_next_char_by_prefix() -> anyhow::Result<()> {
 
 #[test]
 fn search_authors_by_prefix() -> anyhow::Result<()> {
-    let api = OpdsApi::try_from(DATABASE)?;
+    let api = OpdsApi::try_from(DATABASE.as_str())?;
     let result = api.search_authors_by_prefix(&String::from("Александр"))?;
 
     assert_eq!(
@@ -26,7 +26,7 @@ fn search_authors_by_prefix() -> anyhow::Result<()> {
 
 #[test]
 fn authors_by_books_ids() -> anyhow::Result<()> {
-    let api = OpdsApi::try_from(DATABASE)?;
+    let api = OpdsApi::try_from(DATABASE.as_str())?;
 
     let strings = api
         .authors_by_books_ids(vec![768409, 768571, 768746, 768750])?
@@ -51,7 +51,7 @@ fn authors_by_books_ids() -> anyhow::Result<()> {
 
 #[test]
 fn authors_by_genre_id() -> anyhow::Result<()> {
-    let api = OpdsApi::try_from(DATABASE)?;
+    let api = OpdsApi::try_from(DATABASE.as_str())?;
 
     let strings = api
         .authors_by_genre_id(24)?
@@ -64,15 +64,15 @@ fn authors_by_genre_id() -> anyhow::Result<()> {
     assert_eq!(
         result,
         vec![
-            "Анатолий Сергеевич Бернацкий",
-            "Александр Владимирович Волков",
-            "Сергей Михайлович Голицын",
-            "Александр Владимирович Мазин",
-            "Александр Викторович Марков",
-            "Говард Пайл",
-            "Джеймс Перкинс",
-            "Джордж Сартон",
-            "Дон Холлуэй"
+            "Анатолий Сергеевич Бернацкий (1 book)",
+            "Александр Владимирович Волков (1 book)",
+            "Сергей Михайлович Голицын (2 books)",
+            "Александр Владимирович Мазин (1 book in 1 series)",
+            "Александр Викторович Марков (1 book)",
+            "Говард Пайл (1 book)",
+            "Джеймс Перкинс (1 book)",
+            "Джордж Сартон (1 book)",
+            "Дон Холлуэй (1 book)"
         ]
     );
 
@@ -81,7 +81,7 @@ fn authors_by_genre_id() -> anyhow::Result<()> {
 
 #[test]
 fn authors_by_last_name() -> anyhow::Result<()> {
-    let api = OpdsApi::try_from(DATABASE)?;
+    let api = OpdsApi::try_from(DATABASE.as_str())?;
     let strings = api
         .authors_by_last_name(&String::from("Кейн"))?
         .into_iter()
@@ -92,14 +92,17 @@ fn authors_by_last_name() -> anyhow::Result<()> {
 
     assert_eq!(
         result,
-        vec![String::from("Адель Кейн"), String::from("Рэйчел Кейн")]
+        vec![
+            String::from("Адель Кейн (2 books in 1 series)"),
+            String::from("Рэйчел Кейн (1 book in 1 series)")
+        ]
     );
     Ok(())
 }
 
 #[test]
 fn author_by_ids() -> anyhow::Result<()> {
-    let api = OpdsApi::try_from(DATABASE)?;
+    let api = OpdsApi::try_from(DATABASE.as_str())?;
     let strings = api
         .author_by_ids(50, 42, 281)?
         .into_iter()
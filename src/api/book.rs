@@ -2,7 +2,7 @@ use super::*;
 
 #[test]
 fn books_next_char_by_prefix() -> anyhow::Result<()> {
-    let api = OpdsApi::try_from(DATABASE)?;
+    let api = OpdsApi::try_from(DATABASE.as_str())?;
     let result = api.books_next_char_by_prefix(&String::from("сТ"))?;
 
     assert_eq!(result, vec!["Ста", "Сто", "Стр"]);
@@ -11,7 +11,7 @@ fn books_next_char_by_prefix() -> anyhow::Result<()> {
 
 #[test]
 fn search_books_by_prefix() -> anyhow::Result<()> {
-    let api = OpdsApi::try_from(DATABASE)?;
+    let api = OpdsApi::try_from(DATABASE.as_str())?;
     let result = api.search_books_by_prefix(&String::from("Ав"))?;
 
     assert_eq!(result, (vec![String::from("Авиатрисы")], vec![]));
@@ -20,7 +20,7 @@ fn search_books_by_prefix() -> anyhow::Result<()> {
 
 #[test]
 fn books_by_genre_id_and_date() -> anyhow::Result<()> {
-    let api = OpdsApi::try_from(DATABASE)?;
+    let api = OpdsApi::try_from(DATABASE.as_str())?;
 
     let strings = api
         .books_by_genre_id_and_date(24, String::from("2024-06-0%"))?
@@ -40,7 +40,7 @@ fn books_by_genre_id_and_date() -> anyhow::Result<()> {
 
 #[test]
 fn books_by_author_ids() -> anyhow::Result<()> {
-    let api = OpdsApi::try_from(DATABASE)?;
+    let api = OpdsApi::try_from(DATABASE.as_str())?;
 
     let strings = api
         .books_by_author_ids(43, 2, 184)?
@@ -62,7 +62,7 @@ fn books_by_author_ids() -> anyhow::Result<()> {
 
 #[test]
 fn books_by_author_ids_and_serie_id() -> anyhow::Result<()> {
-    let api = OpdsApi::try_from(DATABASE)?;
+    let api = OpdsApi::try_from(DATABASE.as_str())?;
 
     let strings = api
         .books_by_author_ids_and_serie_id(43, 2, 184, 30)?
@@ -82,7 +82,7 @@ fn books_by_author_ids_and_serie_id() -> anyhow::Result<()> {
 
 #[test]
 fn books_by_author_ids_without_serie() -> anyhow::Result<()> {
-    let api = OpdsApi::try_from(DATABASE)?;
+    let api = OpdsApi::try_from(DATABASE.as_str())?;
 
     let strings = api
         .books_by_author_ids_without_serie(43, 2, 184)?
@@ -102,7 +102,7 @@ fn books_by_author_ids_without_serie() -> anyhow::Result<()> {
 
 #[test]
 fn books_by_serie_id() -> anyhow::Result<()> {
-    let api = OpdsApi::try_from(DATABASE)?;
+    let api = OpdsApi::try_from(DATABASE.as_str())?;
 
     let strings = api
         .books_by_serie_id(29)?
@@ -122,7 +122,7 @@ fn books_by_serie_id() -> anyhow::Result<()> {
 
 #[test]
 fn book_by_id() -> anyhow::Result<()> {
-    let api = OpdsApi::try_from(DATABASE)?;
+    let api = OpdsApi::try_from(DATABASE.as_str())?;
 
     let strings = api
         .book_by_id(768409)?
@@ -142,7 +142,7 @@ fn book_by_id() -> anyhow::Result<()> {
 
 #[test]
 fn books_by_book_title() -> anyhow::Result<()> {
-    let api = OpdsApi::try_from(DATABASE)?;
+    let api = OpdsApi::try_from(DATABASE.as_str())?;
 
     let strings = api
         .books_by_book_title(&"Авиатрисы".to_owned())?
@@ -159,3 +159,183 @@ fn books_by_book_title() -> anyhow::Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn deleted_books_by_author_ids() -> anyhow::Result<()> {
+    let api = OpdsApi::try_from(IMPORTED.as_str())?;
+
+    let live = api
+        .books_by_author_ids(22, 83, 521)?
+        .into_iter()
+        .map(|a| a.id)
+        .collect::<Vec<_>>();
+    assert_eq!(live, vec![768988]);
+
+    let strings = api
+        .deleted_books_by_author_ids(22, 83, 521)?
+        .into_iter()
+        .map(|a| format!("{a}"))
+        .collect::<Vec<_>>();
+
+    let result = strings.iter().map(|a| a.as_str()).collect::<Vec<_>>();
+
+    assert_eq!(
+        result,
+        vec!["1 Крепостной - Роман Валерьевич Злотников (2024-06-13) [1019.88 KB]"]
+    );
+    assert_eq!(api.deleted_books_count_by_author_ids(22, 83, 521)?, 1);
+    assert_eq!(api.deleted_books_count_by_author_ids(43, 2, 184)?, 0);
+
+    Ok(())
+}
+
+#[test]
+fn deleted_books_by_serie_id() -> anyhow::Result<()> {
+    let api = OpdsApi::try_from(IMPORTED.as_str())?;
+
+    assert!(api.books_by_serie_id(182)?.is_empty());
+    assert!(api.series_by_author_ids(439, 51, 839)?.is_empty());
+    assert_eq!(api.book_by_id(769304)?, None);
+
+    let strings = api
+        .deleted_books_by_serie_id(182)?
+        .into_iter()
+        .map(|a| format!("{a}"))
+        .collect::<Vec<_>>();
+
+    let result = strings.iter().map(|a| a.as_str()).collect::<Vec<_>>();
+
+    assert_eq!(
+        result,
+        vec!["2 Дракон цвета смерти - Галина Дмитриевна Гончарова (2024-06-26) [1.39 MB]"]
+    );
+    assert_eq!(api.deleted_books_count_by_serie_id(182)?, 1);
+    assert_eq!(api.deleted_books_count_by_serie_id(29)?, 0);
+
+    Ok(())
+}
+
+#[test]
+fn book_with_coauthors() -> anyhow::Result<()> {
+    let api = OpdsApi::try_from(DATABASE.as_str())?;
+
+    let expected =
+        "Три богатыря и Пуп Земли - Александра Шоха, Александр Ионович Боярский (2024-06-01) [3.77 MB]";
+    assert_eq!(
+        api.book_by_id(768398)?.map(|book| book.to_string()),
+        Some(String::from(expected))
+    );
+
+    for (fid, mid, lid) in [(15, 2, 15), (11, 8, 16)] {
+        let strings = api
+            .books_by_author_ids(fid, mid, lid)?
+            .into_iter()
+            .map(|a| format!("{a}"))
+            .collect::<Vec<_>>();
+        assert_eq!(strings, vec![expected]);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn genres_by_book_id() -> anyhow::Result<()> {
+    let api = OpdsApi::try_from(DATABASE.as_str())?;
+
+    let genres = api.genres_by_book_id(768868)?;
+    assert_eq!(
+        genres,
+        vec![
+            Genre::new(16, "prose_contemporary", "Современная русская и зарубежная проза", "Проза"),
+            Genre::new(119, "roman", "Роман", "Проза"),
+        ]
+    );
+    assert!(api.genres_by_book_id(1)?.is_empty());
+    Ok(())
+}
+
+#[test]
+fn genres_by_books_ids() -> anyhow::Result<()> {
+    let api = OpdsApi::try_from(DATABASE.as_str())?;
+
+    let genres = api.genres_by_books_ids(vec![768868, 768919])?;
+    let names = |id: u32| {
+        genres[&id]
+            .iter()
+            .map(|genre| genre.to_string())
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(genres.len(), 2);
+    assert_eq!(
+        names(768919),
+        vec![
+            "Литература для детей / Сказки народов мира",
+            "Литература для детей / Проза для детей"
+        ]
+    );
+    Ok(())
+}
+
+#[test]
+fn books_added_between() -> anyhow::Result<()> {
+    let api = OpdsApi::try_from(DATABASE.as_str())?;
+
+    let books = api.books_added_between("2024-06-28".parse()?, "2024-06-30".parse()?)?;
+    assert_eq!(books.len(), 29 + 23 + 27);
+    assert!(books.windows(2).all(|pair| pair[0].added >= pair[1].added));
+    assert_eq!(
+        books
+            .iter()
+            .take(3)
+            .map(|book| (book.id, book.added.as_str()))
+            .collect::<Vec<_>>(),
+        vec![
+            (769424, "2024-06-30"),
+            (769413, "2024-06-30"),
+            (769435, "2024-06-30")
+        ]
+    );
+
+    let from = Date::new(2024, 7, 1)?;
+    assert!(api.books_added_between(from, from.days_before(1))?.is_empty());
+    Ok(())
+}
+
+#[test]
+fn books_added_in_last_days() -> anyhow::Result<()> {
+    let api = OpdsApi::try_from(DATABASE.as_str())?;
+
+    assert_eq!(
+        api.last_days(3)?,
+        Some((Date::new(2024, 6, 28)?, Date::new(2024, 6, 30)?))
+    );
+    assert_eq!(api.books_added_in_last_days(1)?.len(), 27);
+    assert_eq!(api.books_added_in_last_days(3)?.len(), 29 + 23 + 27);
+    assert!(api.books_added_in_last_days(0)?.is_empty());
+
+    let page = api.books_added_in_last_days_page(3, PageRequest::new(70, 10))?;
+    assert_eq!(page.total, 79);
+    assert_eq!(page.items.len(), 9);
+    assert_eq!(page.next, None);
+    Ok(())
+}
+
+#[test]
+fn authors_with_separators() -> anyhow::Result<()> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("separators.db");
+    std::fs::copy(&*SAMPLE, &path)?;
+
+    let api = OpdsApi::try_from(path.to_string_lossy().as_ref())?;
+    api.conn
+        .execute("UPDATE first_names SET value = 'Анна, Мария:' WHERE id = 43", [])?;
+
+    let books = api.books_by_author_ids(43, 2, 184)?;
+    assert_eq!(books.len(), 2);
+    for book in books {
+        assert_eq!(book.authors.len(), 1);
+        assert_eq!(book.authors[0].first_name, Value::new(43, "Анна, Мария:"));
+        assert_eq!(book.authors[0].last_name.id, 184);
+    }
+    Ok(())
+}
@@ -0,0 +1,28 @@
+use super::*;
+use crate::export::{read_json_lines, write_json_lines};
+
+#[test]
+fn books_by_serie_id() -> anyhow::Result<()> {
+    let api = OpdsApi::try_from(DATABASE.as_str())?;
+    let books = api.books_by_serie_id(29)?;
+
+    let mut out = Vec::new();
+    assert_eq!(write_json_lines(&mut out, &books)?, books.len());
+    assert_eq!(out.iter().filter(|&&b| b == b'\n').count(), books.len());
+    assert_eq!(read_json_lines::<_, Book>(out.as_slice())?, books);
+    Ok(())
+}
+
+#[test]
+fn authors_page() -> anyhow::Result<()> {
+    let api = OpdsApi::try_from(DATABASE.as_str())?;
+    let page = api.authors_by_genre_id_page(24, PageRequest::new(0, 3))?;
+
+    let mut out = Vec::new();
+    write_json_lines(&mut out, std::slice::from_ref(&page))?;
+    assert_eq!(
+        read_json_lines::<_, Page<AuthorInfo>>(out.as_slice())?,
+        vec![page]
+    );
+    Ok(())
+}
@@ -0,0 +1,99 @@
+use super::*;
+use crate::importer::Import;
+
+fn write_inp(dir: &tempfile::TempDir, name: &str, lines: &[&str]) -> anyhow::Result<String> {
+    let path = dir.path().join(name);
+    std::fs::write(&path, lines.join("\r\n"))?;
+    Ok(path.to_string_lossy().into_owned())
+}
+
+#[test]
+fn import_inp() -> anyhow::Result<()> {
+    let mut api = OpdsApi::try_from(":memory:")?;
+    let count = api.import_inp(INP)?;
+    assert_eq!(count, 962);
+
+    let origin = OpdsApi::try_from(DATABASE.as_str())?;
+    assert_eq!(api.meta_genres()?, origin.meta_genres()?);
+    assert_eq!(api.authors_by_genre_id(24)?, origin.authors_by_genre_id(24)?);
+    assert_eq!(api.series_by_genre_id(24)?, origin.series_by_genre_id(24)?);
+    assert_eq!(api.books_by_serie_id(29)?, origin.books_by_serie_id(29)?);
+    assert_eq!(
+        api.books_by_author_ids(43, 2, 184)?,
+        origin.books_by_author_ids(43, 2, 184)?
+    );
+    assert_eq!(
+        api.imports()?,
+        vec![Import {
+            name: String::from("fb2-768381-769440.inp"),
+            first: 768381,
+            last: 769440,
+            count: 962
+        }]
+    );
+    Ok(())
+}
+
+#[test]
+fn import_inp_incremental() -> anyhow::Result<()> {
+    let content = std::fs::read_to_string(INP)?;
+    let lines = content.lines().collect::<Vec<_>>();
+    let dir = tempfile::tempdir()?;
+    let head = write_inp(&dir, "fb2-768381-768900.inp", &lines[..500])?;
+    let tail = write_inp(&dir, "fb2-768850-769440.inp", &lines[450..])?;
+
+    let mut api = OpdsApi::try_from(":memory:")?;
+    assert_eq!(api.import_inp(head)?, 500);
+    assert_eq!(api.import_inp(&tail)?, 462);
+    assert_eq!(api.import_inp(&tail)?, 0);
+
+    let origin = OpdsApi::try_from(DATABASE.as_str())?;
+    assert_eq!(api.authors_by_genre_id(24)?, origin.authors_by_genre_id(24)?);
+    assert_eq!(api.series_by_genre_id(24)?, origin.series_by_genre_id(24)?);
+    assert_eq!(
+        api.series_by_author_ids(50, 42, 281)?,
+        origin.series_by_author_ids(50, 42, 281)?
+    );
+    assert_eq!(
+        api.books_by_author_ids(43, 2, 184)?,
+        origin.books_by_author_ids(43, 2, 184)?
+    );
+    assert_eq!(
+        api.imports()?
+            .into_iter()
+            .map(|import| (import.first, import.last, import.count))
+            .collect::<Vec<_>>(),
+        vec![(768381, 768900, 500), (768850, 769440, 462)]
+    );
+
+    let index = |api: &OpdsApi| -> anyhow::Result<Vec<(String, String)>> {
+        let mut statement = api
+            .conn
+            .prepare("SELECT kind, name FROM search_index ORDER BY kind, name, id, lid, fid, mid")?;
+        let rows = statement.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?;
+        Ok(rows.collect::<rusqlite::Result<_>>()?)
+    };
+    assert_eq!(index(&api)?, index(&origin)?);
+    let imported = OpdsApi::try_from(IMPORTED.as_str())?;
+    assert_eq!(api.search("дракон", 10)?, imported.search("дракон", 10)?);
+    Ok(())
+}
+
+#[test]
+fn import_inp_rollback() -> anyhow::Result<()> {
+    let content = std::fs::read_to_string(INP)?;
+    let mut lines = content.lines().collect::<Vec<_>>();
+    let dir = tempfile::tempdir()?;
+    let head = write_inp(&dir, "fb2-768381-768900.inp", &lines[..500])?;
+    lines[700] = "broken record";
+    let tail = write_inp(&dir, "fb2-768850-769440.inp", &lines[450..])?;
+
+    let mut api = OpdsApi::try_from(":memory:")?;
+    assert_eq!(api.import_inp(head)?, 500);
+    assert!(api.import_inp(tail).is_err());
+
+    assert_eq!(api.book_by_id(768409)?.map(|book| book.id), Some(768409));
+    assert_eq!(api.book_by_id(769440)?, None);
+    assert_eq!(api.imports()?.len(), 1);
+    Ok(())
+}
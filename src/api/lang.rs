@@ -0,0 +1,98 @@
+use super::*;
+
+#[test]
+fn languages() -> anyhow::Result<()> {
+    let api = OpdsApi::try_from(IMPORTED.as_str())?;
+
+    let languages = api.languages()?;
+    assert_eq!(
+        languages,
+        vec![
+            (String::from("ru"), 952),
+            (String::from("en"), 5),
+            (String::from("uk"), 2),
+            (String::from("be"), 1),
+        ]
+    );
+    Ok(())
+}
+
+#[test]
+fn book_lang() -> anyhow::Result<()> {
+    let api = OpdsApi::try_from(DATABASE.as_str())?;
+
+    let book = api.book_by_id(768919)?.expect("book");
+    assert_eq!(book.lang, "uk");
+    let book = api.book_by_id(768866)?.expect("book");
+    assert_eq!(book.lang, "en");
+    Ok(())
+}
+
+#[test]
+fn books_by_genre_id_in_languages() -> anyhow::Result<()> {
+    let api = OpdsApi::try_from(DATABASE.as_str())?;
+    let ids = |api: &OpdsApi| -> anyhow::Result<Vec<u32>> {
+        let books = api.books_by_genre_id_and_date(41, String::from("%"))?;
+        Ok(books.into_iter().map(|book| book.id).collect())
+    };
+    assert_eq!(ids(&api)?.len(), 7);
+
+    api.set_languages(&["EN"])?;
+    assert_eq!(ids(&api)?, vec![768866, 768876]);
+
+    api.set_languages(&["uk", "be"])?;
+    assert!(ids(&api)?.is_empty());
+
+    // unknown codes are refused and the filter is kept
+    assert!(matches!(
+        api.set_languages(&["en", "xx"]),
+        Err(Error::InvalidArgument(what)) if what == "Unknown languages: xx"
+    ));
+    assert!(ids(&api)?.is_empty());
+
+    api.set_languages::<&str>(&[])?;
+    assert_eq!(ids(&api)?.len(), 7);
+    Ok(())
+}
+
+#[test]
+fn authors_and_series_in_languages() -> anyhow::Result<()> {
+    let api = OpdsApi::try_from(DATABASE.as_str())?;
+    api.set_languages(&["en"])?;
+
+    let authors = api.authors_by_genre_id(41)?;
+    let names = authors
+        .iter()
+        .map(|info| info.author.last_name.value.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(names, vec!["Гарднер", "Куин"]);
+
+    let series = api.series_by_genre_id(41)?;
+    assert_eq!(
+        series.iter().map(|serie| serie.id).collect::<Vec<_>>(),
+        vec![109, 107]
+    );
+
+    let found = api.search_authors_by_prefix(&String::from("Кэрролл"))?;
+    assert_eq!(found, (vec![], vec![]));
+    api.set_languages(&["uk"])?;
+    let found = api.search_authors_by_prefix(&String::from("Кэрролл"))?;
+    assert_eq!(found, (vec![String::from("Кэрролл")], vec![]));
+    Ok(())
+}
+
+#[test]
+fn lookups_in_languages() -> anyhow::Result<()> {
+    let api = OpdsApi::try_from(DATABASE.as_str())?;
+    let book = api.book_by_id(768866)?.expect("book");
+    let sid = book.sid.expect("serie");
+    assert_eq!(api.series_by_ids(vec![sid])?.len(), 1);
+    assert!(!api.authors_by_books_ids(vec![book.id])?.is_empty());
+
+    api.set_languages(&["uk"])?;
+    assert_eq!(api.book_by_id(768866)?, None);
+    assert!(api.series_by_ids(vec![sid])?.is_empty());
+    assert!(api.authors_by_books_ids(vec![book.id])?.is_empty());
+    assert!(api.book_by_id(768919)?.is_some());
+    Ok(())
+}
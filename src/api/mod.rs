@@ -1,21 +1,107 @@
 use super::*;
 
+#[cfg(feature = "async")]
+mod async_api;
 mod author;
 mod book;
+#[cfg(feature = "serde")]
+mod export;
+mod importer;
+mod lang;
+mod opds;
+mod page;
+mod pool;
+mod router;
+mod schema;
+mod search;
 mod serie;
+mod validate;
+
+lazy_static::lazy_static! {
+    /// Copy of the sample database migrated to the current schema, the sample stays untouched
+    static ref SAMPLE: std::path::PathBuf = migrated_sample().expect("migrated sample database");
+    /// Read-only URI of the migrated sample database
+    static ref DATABASE: String = format!("file:{}?mode=ro", SAMPLE.display());
+    /// Read-only URI of the database imported from the sample INP, it has the deleted books
+    static ref IMPORTED: String = imported_sample().expect("imported sample database");
+}
+
+const INP: &str = "data/fb2-768381-769440.inp";
+
+fn migrated_sample() -> anyhow::Result<std::path::PathBuf> {
+    fixture("sample.db", |path| {
+        std::fs::copy("data/fb2-768381-769440.db", path)?;
+        OpdsApi::open_migrated(&path.to_string_lossy())?;
+        Ok(())
+    })
+}
 
-const DATABASE: &'static str = "file:data/fb2-768381-769440.db?mode=ro";
+fn imported_sample() -> anyhow::Result<String> {
+    let path = fixture("imported.db", |path| {
+        OpdsApi::try_from(path.to_string_lossy().as_ref())?.import_inp(INP)?;
+        Ok(())
+    })?;
+    Ok(format!("file:{}?mode=ro", path.display()))
+}
+
+/// Builds the database next to the test binary and replaces the one of the previous run
+///
+/// Statics are never dropped, so a `TempDir` kept in one would be left in the temp directory
+/// on every run. The database is built in a scratch directory and renamed into place,
+/// so a concurrent run of the same binary keeps reading its own copy.
+fn fixture<F>(name: &str, build: F) -> anyhow::Result<std::path::PathBuf>
+where
+    F: FnOnce(&std::path::Path) -> anyhow::Result<()>,
+{
+    let exe = std::env::current_exe()?;
+    let dir = exe.parent().unwrap_or(std::path::Path::new("."));
+    let stem = exe.file_stem().unwrap_or_default().to_string_lossy();
+    let scratch = tempfile::Builder::new().prefix(stem.as_ref()).tempdir_in(dir)?;
+    let built = scratch.path().join(name);
+    build(&built)?;
+    let path = dir.join(format!("{stem}-{name}"));
+    std::fs::rename(built, &path)?;
+    Ok(path)
+}
 
 #[test]
 fn is_readonly() -> anyhow::Result<()> {
-    let api = OpdsApi::try_from(DATABASE)?;
+    let api = OpdsApi::try_from(DATABASE.as_str())?;
     assert!(api.is_readonly()?);
     Ok(())
 }
 
+#[test]
+fn collations() -> anyhow::Result<()> {
+    let api = OpdsApi::try_from(":memory:")?;
+    let sorted = |collation: &str| -> anyhow::Result<Vec<String>> {
+        let sql = format!(
+            "SELECT column1 FROM (VALUES ('Том 10'), ('ауэрбах'), ('Том 2'), ('Яковлев'))
+            ORDER BY column1 COLLATE {collation}"
+        );
+        let mut statement = api.conn.prepare(&sql)?;
+        let rows = statement.query_map([], |row| row.get(0))?;
+        Ok(rows.collect::<rusqlite::Result<_>>()?)
+    };
+
+    assert_eq!(
+        sorted("opds")?,
+        vec!["Том 10", "Том 2", "Яковлев", "ауэрбах"]
+    );
+    assert_eq!(
+        sorted("opds_ci")?,
+        vec!["ауэрбах", "Том 10", "Том 2", "Яковлев"]
+    );
+    assert_eq!(
+        sorted("opds_natural")?,
+        vec!["ауэрбах", "Том 2", "Том 10", "Яковлев"]
+    );
+    Ok(())
+}
+
 #[test]
 fn meta_genres() -> anyhow::Result<()> {
-    let api = OpdsApi::try_from(DATABASE)?;
+    let api = OpdsApi::try_from(DATABASE.as_str())?;
 
     let strings = api.meta_genres()?;
     let result = strings.iter().map(|a| a.as_str()).collect::<Vec<_>>();
@@ -54,7 +140,7 @@ fn meta_genres() -> anyhow::Result<()> {
 
 #[test]
 fn genres_by_meta() -> anyhow::Result<()> {
-    let api = OpdsApi::try_from(DATABASE)?;
+    let api = OpdsApi::try_from(DATABASE.as_str())?;
 
     let result = api.genres_by_meta(&String::from("Деловая литература"))?;
 
@@ -74,7 +160,7 @@ fn genres_by_meta() -> anyhow::Result<()> {
     Ok(())
 }
 
-fn fetcher(mask: &String) -> anyhow::Result<Vec<String>> {
+fn fetcher(mask: &String) -> Result<Vec<String>> {
     let out = match mask.as_str() {
         "A" => vec!["A", "Ab", "Ac"],
         "B" => vec!["B", "BB"],
@@ -88,9 +174,9 @@ fn fetcher(mask: &String) -> anyhow::Result<Vec<String>> {
         _ => vec![],
     };
     if out.is_empty() {
-        Err(anyhow::anyhow!("Unexpected mask '{mask}'"))
+        Err(Error::InvalidArgument(format!("Unexpected mask '{mask}'")))
     } else {
-        Ok(out.into_iter().map(|s| String::from(s)).collect())
+        Ok(out.into_iter().map(String::from).collect())
     }
 }
 
@@ -131,5 +217,3 @@ fn search_by_mask_c() -> anyhow::Result<()> {
     assert_eq!(empty, tail.iter().map(|a| a.as_str()).collect::<Vec<_>>());
     Ok(())
 }
-
-
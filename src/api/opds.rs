@@ -0,0 +1,283 @@
+use super::*;
+use crate::opds::{Catalog, Kind};
+
+#[test]
+fn root() -> anyhow::Result<()> {
+    let api = OpdsApi::try_from(DATABASE.as_str())?;
+    let feed = Catalog::new(&api, "/opds").root()?;
+
+    assert_eq!(feed.id, "urn:opds_api:root");
+    assert_eq!(feed.updated, "2024-06-30T00:00:00Z");
+    assert_eq!(
+        feed.entries
+            .iter()
+            .map(|e| e.links[0].href.as_str())
+            .collect::<Vec<_>>(),
+        vec![
+            "/opds/authors/",
+            "/opds/series/",
+            "/opds/titles/",
+            "/opds/genres",
+            "/opds/new"
+        ]
+    );
+    Ok(())
+}
+
+#[test]
+fn authors() -> anyhow::Result<()> {
+    let api = OpdsApi::try_from(DATABASE.as_str())?;
+    let feed = Catalog::new(&api, "/opds/").authors(&String::from("Александр"))?;
+
+    assert_eq!(feed.kind, Kind::Navigation);
+    assert_eq!(
+        feed.entries
+            .iter()
+            .map(|e| (e.title.as_str(), e.links[0].href.as_str()))
+            .collect::<Vec<_>>(),
+        vec![
+            (
+                "Александров",
+                "/opds/last_name/%D0%90%D0%BB%D0%B5%D0%BA%D1%81%D0%B0%D0%BD%D0%B4%D1%80%D0%BE%D0%B2"
+            ),
+            (
+                "Александрова",
+                "/opds/last_name/%D0%90%D0%BB%D0%B5%D0%BA%D1%81%D0%B0%D0%BD%D0%B4%D1%80%D0%BE%D0%B2%D0%B0"
+            )
+        ]
+    );
+    Ok(())
+}
+
+#[test]
+fn authors_by_last_name() -> anyhow::Result<()> {
+    let api = OpdsApi::try_from(DATABASE.as_str())?;
+    let feed = Catalog::new(&api, "/opds").authors_by_last_name(&String::from("Кейн"))?;
+
+    assert_eq!(
+        feed.entries
+            .iter()
+            .map(|e| e.title.as_str())
+            .collect::<Vec<_>>(),
+        vec!["Адель Кейн", "Рэйчел Кейн"]
+    );
+    assert_eq!(
+        feed.entries
+            .iter()
+            .map(|e| e.content.as_deref())
+            .collect::<Vec<_>>(),
+        vec![Some("2 books in 1 series"), Some("1 book in 1 series")]
+    );
+    Ok(())
+}
+
+#[test]
+fn author() -> anyhow::Result<()> {
+    let api = OpdsApi::try_from(DATABASE.as_str())?;
+    let feed = Catalog::new(&api, "/opds").author(43, 2, 184)?;
+
+    assert_eq!(feed.kind, Kind::Acquisition);
+    assert_eq!(feed.id, "urn:opds_api:author:43:2:184");
+    assert_eq!(feed.title, "Анна Велес");
+    assert!(feed.entries.is_empty());
+    assert_eq!(
+        feed.groups
+            .iter()
+            .map(|g| (
+                g.title.as_str(),
+                g.href.as_deref(),
+                g.entries
+                    .iter()
+                    .map(|e| (e.id.as_str(), e.updated.as_str()))
+                    .collect::<Vec<_>>()
+            ))
+            .collect::<Vec<_>>(),
+        vec![
+            (
+                "Without serie",
+                None,
+                vec![("urn:opds_api:book:769016", "2024-06-18T00:00:00Z")]
+            ),
+            (
+                "По следам городских легенд",
+                Some("/opds/serie/30"),
+                vec![("urn:opds_api:book:768524", "2024-06-05T00:00:00Z")]
+            )
+        ]
+    );
+    Ok(())
+}
+
+#[test]
+fn serie() -> anyhow::Result<()> {
+    let api = OpdsApi::try_from(DATABASE.as_str())?;
+    let xml = Catalog::new(&api, "/opds").serie(29)?.to_string();
+
+    assert!(xml.starts_with(
+        r#"<?xml version="1.0" encoding="UTF-8"?><feed xmlns="http://www.w3.org/2005/Atom""#
+    ));
+    assert!(xml.contains("<id>urn:opds_api:serie:29</id>"));
+    assert!(xml.contains(
+        r#"<link rel="self" href="/opds/serie/29" type="application/atom+xml;profile=opds-catalog;kind=acquisition"/>"#
+    ));
+    assert!(xml.contains(
+        "<entry><id>urn:opds_api:book:768522</id><title>Трон змей</title><updated>2024-06-05T00:00:00Z</updated>"
+    ));
+    assert!(xml.contains("<author><name>Фрост Кей</name><uri>/opds/author/"));
+    assert!(xml.contains("<dc:language>ru</dc:language>"));
+    assert!(xml.contains(r#"<category term="sf_fantasy" label="Фэнтези"/>"#));
+    assert!(xml.contains(
+        r#"<link rel="http://opds-spec.org/acquisition/open-access" href="/opds/book/768522/fb2" type="application/x-fictionbook+xml"/>"#
+    ));
+    assert!(xml.ends_with("</entry></feed>"));
+    Ok(())
+}
+
+#[test]
+fn genres() -> anyhow::Result<()> {
+    let api = OpdsApi::try_from(DATABASE.as_str())?;
+    let catalog = Catalog::new(&api, "/opds");
+
+    let meta = catalog.meta_genres()?;
+    assert_eq!(meta.entries.len(), 23);
+    assert_eq!(
+        meta.entries[0].links[0].href,
+        "/opds/genres/%D0%94%D0%B5%D0%BB%D0%BE%D0%B2%D0%B0%D1%8F%20%D0%BB%D0%B8%D1%82%D0%B5%D1%80%D0%B0%D1%82%D1%83%D1%80%D0%B0"
+    );
+
+    let genres = catalog.genres(&String::from("Деловая литература"))?;
+    assert_eq!(
+        genres
+            .entries
+            .iter()
+            .map(|e| e.links[0].href.as_str())
+            .collect::<Vec<_>>(),
+        vec![
+            "/opds/genre/47",
+            "/opds/genre/44",
+            "/opds/genre/48",
+            "/opds/genre/120"
+        ]
+    );
+
+    let books = catalog.genre_books(24)?;
+    assert_eq!(books.kind, Kind::Acquisition);
+    assert_eq!(
+        books.entries.len(),
+        api.books_by_genre_id_and_date(24, String::from("%"))?.len()
+    );
+    Ok(())
+}
+
+#[test]
+fn root_json() -> anyhow::Result<()> {
+    let api = OpdsApi::try_from(DATABASE.as_str())?;
+    let json = opds2::render(&Catalog::new(&api, "/opds2").root()?);
+
+    assert!(json.starts_with(
+        r#"{"metadata":{"identifier":"urn:opds_api:root","title":"Catalog","modified":"2024-06-30T00:00:00Z"}"#
+    ));
+    assert!(json.contains(
+        r#""navigation":[{"href":"/opds2/authors/","title":"Authors","type":"application/opds+json","rel":"subsection"}"#
+    ));
+    assert!(!json.contains("publications"));
+    Ok(())
+}
+
+#[test]
+fn serie_json() -> anyhow::Result<()> {
+    let api = OpdsApi::try_from(DATABASE.as_str())?;
+    let json = opds2::render(&Catalog::new(&api, "/opds2").serie(29)?);
+
+    assert!(json.contains(
+        r#""publications":[{"metadata":{"@type":"http://schema.org/Book","identifier":"urn:opds_api:book:768522","title":"Трон змей""#
+    ));
+    assert!(json.contains(r#""author":[{"name":"Фрост Кей","links":[{"href":"/opds2/author/"#));
+    assert!(json.contains(r#""belongsTo":{"series":[{"name":"Драконьи Острова","position":3"#));
+    assert!(!json.contains("navigation"));
+    Ok(())
+}
+
+#[test]
+fn search() -> anyhow::Result<()> {
+    let api = OpdsApi::try_from(IMPORTED.as_str())?;
+    let catalog = Catalog::new(&api, "/opds");
+
+    let feed = catalog.search(&String::from("дракон"))?;
+    let hits = api.search_page("дракон", PageRequest::default())?;
+    assert_eq!(feed.kind, Kind::Acquisition);
+    assert_eq!(feed.title, format!("Search: дракон [{}]", hits.total));
+    assert_eq!(
+        feed.links[2].href, "/opds/opensearch.xml",
+        "feed advertises the search"
+    );
+    // branches of the term come first, the one of the authors has no matches
+    let branches = feed.entries[..2]
+        .iter()
+        .map(|e| (e.title.as_str(), e.links[0].href.as_str()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        branches,
+        vec![
+            ("Series", "/opds/series/%D0%B4%D1%80%D0%B0%D0%BA%D0%BE%D0%BD"),
+            ("Titles", "/opds/titles/%D0%B4%D1%80%D0%B0%D0%BA%D0%BE%D0%BD")
+        ]
+    );
+    assert_eq!(feed.entries.len(), hits.items.len() + 2);
+    assert!(feed.entries[2].title.starts_with("Драконьи Острова ["));
+    assert!(feed.entries[2].links[0].href.starts_with("/opds/serie/"));
+    assert_eq!(feed.entries[3].title, "Алиана, спасительница драконов");
+    assert_eq!(feed.entries[3].links[0].href, "/opds/book/768382/fb2");
+
+    let feed = catalog.search(&String::from("Злотников"))?;
+    assert_eq!(
+        feed.entries[1..]
+            .iter()
+            .map(|e| (e.title.as_str(), e.links[0].href.as_str()))
+            .collect::<Vec<_>>(),
+        vec![("Роман Валерьевич Злотников", "/opds/author/22/83/521")]
+    );
+
+    let feed = catalog.search(&String::from("  "))?;
+    assert_eq!(feed.title, "Search:    [0]");
+    assert!(feed.entries.is_empty());
+    Ok(())
+}
+
+#[test]
+fn opensearch() -> anyhow::Result<()> {
+    let api = OpdsApi::try_from(DATABASE.as_str())?;
+    let document = Catalog::new(&api, "/opds").opensearch(crate::opds::NAVIGATION);
+
+    assert!(document
+        .contains(r#"<OpenSearchDescription xmlns="http://a9.com/-/spec/opensearch/1.1/">"#));
+    assert!(document.contains(r#"template="/opds/search/{searchTerms}""#));
+    Ok(())
+}
+
+#[test]
+fn new_arrivals() -> anyhow::Result<()> {
+    let api = OpdsApi::try_from(DATABASE.as_str())?;
+    let catalog = Catalog::new(&api, "/opds");
+
+    let feed = catalog.new_arrivals()?;
+    assert_eq!(feed.kind, Kind::Acquisition);
+    assert!(feed.entries.is_empty());
+    assert_eq!(
+        feed.groups
+            .iter()
+            .map(|g| (g.title.as_str(), g.href.as_deref(), g.entries.len()))
+            .collect::<Vec<_>>(),
+        vec![
+            ("2024-06-30", Some("/opds/new/2024-06-30"), 27),
+            ("2024-06-29", Some("/opds/new/2024-06-29"), 23),
+        ]
+    );
+    assert_eq!(feed.links.last().map(|l| l.href.as_str()), Some("/opds/new?page=3"));
+
+    let feed = catalog.new_arrivals_of("2024-06-29".parse()?)?;
+    assert_eq!(feed.id, "urn:opds_api:new:2024-06-29");
+    assert_eq!(feed.entries.len(), 23);
+    Ok(())
+}
+
@@ -0,0 +1,141 @@
+use super::*;
+
+/// Collects all pages by following the next cursors
+fn pages<T, F>(limit: u32, fetch: F) -> anyhow::Result<(Vec<T>, u32, usize)>
+where
+    F: Fn(PageRequest) -> Result<Page<T>>,
+{
+    let mut request = Some(PageRequest::new(0, limit));
+    let mut items = Vec::new();
+    let mut total = 0;
+    let mut count = 0;
+    while let Some(page) = request {
+        let mut page = fetch(page)?;
+        assert!(page.items.len() <= limit as usize);
+        total = page.total;
+        count += 1;
+        items.append(&mut page.items);
+        request = page.next;
+    }
+    Ok((items, total, count))
+}
+
+#[test]
+fn authors_by_genre_id_page() -> anyhow::Result<()> {
+    let api = OpdsApi::try_from(DATABASE.as_str())?;
+    let expected = api.authors_by_genre_id(24)?;
+
+    let (items, total, count) = pages(3, |page| api.authors_by_genre_id_page(24, page))?;
+    assert_eq!(items, expected);
+    assert_eq!(total as usize, expected.len());
+    assert_eq!(count, expected.len().div_ceil(3));
+    Ok(())
+}
+
+#[test]
+fn search_page() -> anyhow::Result<()> {
+    let api = OpdsApi::try_from(DATABASE.as_str())?;
+    let expected = api.search("дракон", 1000)?;
+
+    let (items, total, _) = pages(4, |page| api.search_page("дракон", page))?;
+    assert_eq!(items, expected);
+    assert_eq!(total as usize, expected.len());
+    assert_eq!(api.search_page("  ", PageRequest::default())?.total, 0);
+    Ok(())
+}
+
+#[test]
+fn series_by_genre_id_page() -> anyhow::Result<()> {
+    let api = OpdsApi::try_from(DATABASE.as_str())?;
+    let expected = api.series_by_genre_id(24)?;
+
+    let (items, total, _) = pages(2, |page| api.series_by_genre_id_page(24, page))?;
+    assert_eq!(items, expected);
+    assert_eq!(total as usize, expected.len());
+    Ok(())
+}
+
+#[test]
+fn books_by_genre_id_and_date_page() -> anyhow::Result<()> {
+    let api = OpdsApi::try_from(DATABASE.as_str())?;
+    let date = String::from("%");
+    let expected = api.books_by_genre_id_and_date(24, date.clone())?;
+
+    let (items, total, _) = pages(5, |page| {
+        api.books_by_genre_id_and_date_page(24, date.clone(), page)
+    })?;
+    assert_eq!(items, expected);
+    assert_eq!(total as usize, expected.len());
+    Ok(())
+}
+
+#[test]
+fn books_by_author_ids_page() -> anyhow::Result<()> {
+    let api = OpdsApi::try_from(DATABASE.as_str())?;
+
+    let first = api.books_by_author_ids_page(43, 2, 184, PageRequest::new(0, 1))?;
+    assert_eq!(first.total, 2);
+    assert_eq!(first.prev, None);
+    assert_eq!(first.next, Some(PageRequest::new(1, 1)));
+    assert_eq!(
+        first
+            .items
+            .iter()
+            .map(|b| b.to_string())
+            .collect::<Vec<_>>(),
+        vec!["День писателя - Анна Велес (2024-06-18) [976.19 KB]"]
+    );
+
+    let second = api.books_by_author_ids_page(43, 2, 184, PageRequest::new(1, 1))?;
+    assert_eq!(second.prev, Some(PageRequest::new(0, 1)));
+    assert_eq!(second.next, None);
+    assert_eq!(
+        second
+            .items
+            .iter()
+            .map(|b| b.to_string())
+            .collect::<Vec<_>>(),
+        vec!["2 Хозяин мрачного замка - Анна Велес (2024-06-05) [1.91 MB]"]
+    );
+    Ok(())
+}
+
+#[test]
+fn books_by_serie_id_page() -> anyhow::Result<()> {
+    let api = OpdsApi::try_from(DATABASE.as_str())?;
+    let expected = api.books_by_serie_id(29)?;
+
+    let (items, total, _) = pages(1, |page| api.books_by_serie_id_page(29, page))?;
+    assert_eq!(items, expected);
+    assert_eq!(total as usize, expected.len());
+    Ok(())
+}
+
+#[test]
+fn books_by_author_ids_and_serie_id_page() -> anyhow::Result<()> {
+    let api = OpdsApi::try_from(DATABASE.as_str())?;
+    let books = api.books_by_author_ids(43, 2, 184)?;
+    let sid = books
+        .iter()
+        .find_map(|book| book.sid)
+        .expect("serie of the author");
+
+    let expected = api.books_by_author_ids_and_serie_id(43, 2, 184, sid)?;
+    assert!(!expected.is_empty());
+    assert!(expected.iter().all(|book| book.sid == Some(sid)));
+    let (items, total, _) = pages(1, |page| {
+        api.books_by_author_ids_and_serie_id_page(43, 2, 184, sid, page)
+    })?;
+    assert_eq!(items, expected);
+    assert_eq!(total as usize, expected.len());
+
+    let expected = api.books_by_author_ids_without_serie(43, 2, 184)?;
+    assert!(!expected.is_empty());
+    assert!(expected.iter().all(|book| book.sid.is_none()));
+    let (items, total, _) = pages(1, |page| {
+        api.books_by_author_ids_without_serie_page(43, 2, 184, page)
+    })?;
+    assert_eq!(items, expected);
+    assert_eq!(total as usize, expected.len());
+    Ok(())
+}
@@ -0,0 +1,121 @@
+use super::*;
+
+use std::thread;
+
+#[test]
+fn open() -> anyhow::Result<()> {
+    let pool = OpdsPool::open(&DATABASE, 2)?;
+    assert_eq!(pool.size(), 2);
+
+    assert!(matches!(
+        OpdsPool::open(&DATABASE, 0),
+        Err(Error::InvalidArgument(_))
+    ));
+    assert!(matches!(
+        OpdsPool::open("file:data/missing.db?mode=ro", 1),
+        Err(Error::Sqlite(_))
+    ));
+    Ok(())
+}
+
+#[test]
+fn open_path() -> anyhow::Result<()> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("library?mode=rw#1%20.db");
+    std::fs::copy(&*SAMPLE, &path)?;
+
+    let pool = OpdsPool::open_path(&path, 1)?;
+    assert!(pool.get().is_readonly()?);
+    assert!(pool.get().book_by_id(768866)?.is_some());
+    assert!(matches!(
+        OpdsPool::open_path(dir.path().join("missing.db"), 1),
+        Err(Error::Sqlite(_))
+    ));
+    Ok(())
+}
+
+#[test]
+fn open_migrated() -> anyhow::Result<()> {
+    let legacy = std::path::Path::new("data/fb2-768381-769440.db");
+    assert!(matches!(
+        OpdsPool::open_path(legacy, 1),
+        Err(Error::Schema { version: 0, .. })
+    ));
+
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("legacy.db");
+    std::fs::copy(legacy, &path)?;
+    let pool = OpdsPool::open_migrated(&path, 2)?;
+    assert!(pool.get().is_readonly()?);
+    assert_eq!(pool.get().schema_version()?, crate::schema::VERSION);
+    assert!(pool.get().book_by_id(768866)?.is_some());
+
+    let flags = rusqlite::OpenFlags::SQLITE_OPEN_READ_ONLY;
+    let conn = rusqlite::Connection::open_with_flags(legacy, flags)?;
+    assert_eq!(crate::schema::version(&conn)?, 0);
+    assert!(matches!(
+        OpdsPool::open_migrated(dir.path().join("missing.db"), 1),
+        Err(Error::Sqlite(_))
+    ));
+    assert!(!dir.path().join("missing.db").exists());
+    Ok(())
+}
+
+#[test]
+fn get() -> anyhow::Result<()> {
+    let pool = OpdsPool::open(&DATABASE, 2)?;
+
+    let first = pool.get();
+    let second = pool.get();
+    assert!(pool.try_get().is_none());
+    assert_eq!(first.book_by_id(768866)?, second.book_by_id(768866)?);
+
+    drop(first);
+    assert!(pool.try_get().is_some());
+    Ok(())
+}
+
+#[test]
+fn parallel() -> anyhow::Result<()> {
+    let pool = OpdsPool::open(&DATABASE, 2)?;
+    let expected = pool.get().authors_by_genre_id(24)?;
+
+    let results = thread::scope(|scope| {
+        let handles = (0..8)
+            .map(|_| scope.spawn(|| pool.get().authors_by_genre_id(24)))
+            .collect::<Vec<_>>();
+        handles
+            .into_iter()
+            .map(|handle| handle.join().expect("thread"))
+            .collect::<Result<Vec<_>>>()
+    })?;
+    assert_eq!(results.len(), 8);
+    assert!(results.iter().all(|authors| authors == &expected));
+    Ok(())
+}
+
+#[test]
+fn languages() -> anyhow::Result<()> {
+    let ids = |api: &OpdsApi| -> anyhow::Result<Vec<u32>> {
+        let books = api.books_by_genre_id_and_date(41, String::from("%"))?;
+        Ok(books.into_iter().map(|book| book.id).collect())
+    };
+    let mut pool = OpdsPool::open(&DATABASE, 2)?;
+    pool.set_languages(&["en"])?;
+
+    let (first, second) = (pool.get(), pool.get());
+    for api in [&first, &second] {
+        assert_eq!(ids(api)?, vec![768866, 768876]);
+    }
+
+    // the languages of the taken connection do not leak to the next call
+    first.set_languages::<&str>(&[])?;
+    assert_eq!(ids(&first)?.len(), 7);
+    assert_eq!(ids(&second)?, vec![768866, 768876]);
+    drop((first, second));
+    let (first, second) = (pool.get(), pool.get());
+    for api in [&first, &second] {
+        assert_eq!(ids(api)?, vec![768866, 768876]);
+    }
+    Ok(())
+}
@@ -0,0 +1,282 @@
+use super::*;
+use crate::opds::{ACQUISITION, NAVIGATION};
+use crate::router::Router;
+
+#[test]
+fn root() -> anyhow::Result<()> {
+    let api = OpdsApi::try_from(DATABASE.as_str())?;
+    let response = Router::new(&api).handle("/opds");
+
+    assert_eq!(response.status, 200);
+    assert_eq!(response.mime, NAVIGATION);
+    assert!(String::from_utf8(response.body)?.contains("<id>urn:opds_api:root</id>"));
+    Ok(())
+}
+
+#[test]
+fn authors() -> anyhow::Result<()> {
+    let api = OpdsApi::try_from(DATABASE.as_str())?;
+    let router = Router::new(&api);
+
+    let response = router.handle("/opds/authors/");
+    assert_eq!(response.status, 200);
+
+    let response =
+        router.handle("/opds/authors/%D0%90%D0%BB%D0%B5%D0%BA%D1%81%D0%B0%D0%BD%D0%B4%D1%80");
+    let body = String::from_utf8(response.body)?;
+    assert_eq!(response.status, 200);
+    assert!(body.contains("<title>Александрова</title>"));
+    Ok(())
+}
+
+#[test]
+fn author() -> anyhow::Result<()> {
+    let api = OpdsApi::try_from(DATABASE.as_str())?;
+    let response = Router::new(&api).handle("/opds/author/43/2/184?page=1");
+
+    assert_eq!(response.status, 200);
+    assert_eq!(response.mime, ACQUISITION);
+    assert!(String::from_utf8(response.body)?.contains("<title>Анна Велес</title>"));
+    Ok(())
+}
+
+#[test]
+fn serie_json() -> anyhow::Result<()> {
+    let api = OpdsApi::try_from(DATABASE.as_str())?;
+    let response = Router::new(&api).handle("/opds2/serie/29");
+
+    assert_eq!(response.status, 200);
+    assert_eq!(response.mime, opds2::MIME);
+    assert!(String::from_utf8(response.body)?.starts_with(r#"{"metadata":"#));
+    Ok(())
+}
+
+#[test]
+fn genres() -> anyhow::Result<()> {
+    let api = OpdsApi::try_from(DATABASE.as_str())?;
+    let router = Router::new(&api);
+
+    for target in [
+        "/opds/genres",
+        "/opds/genres/%D0%94%D0%B5%D0%BB%D0%BE%D0%B2%D0%B0%D1%8F%20%D0%BB%D0%B8%D1%82%D0%B5%D1%80%D0%B0%D1%82%D1%83%D1%80%D0%B0",
+        "/opds/genre/24",
+        "/opds/genre/24/authors",
+        "/opds/genre/24/series",
+        "/opds/genre/24/books",
+    ] {
+        assert_eq!(router.handle(target).status, 200, "{target}");
+    }
+    Ok(())
+}
+
+#[test]
+fn errors() -> anyhow::Result<()> {
+    let api = OpdsApi::try_from(DATABASE.as_str())?;
+    let router = Router::new(&api);
+
+    assert_eq!(router.handle("/").status, 404);
+    assert_eq!(router.handle("/opds/unknown").status, 404);
+    assert_eq!(router.handle("/opds/serie/abc").status, 400);
+    assert_eq!(router.handle("/opds/serie/999999").status, 404);
+    assert_eq!(router.handle("/opds/author/999999/999999/999999").status, 404);
+    assert_eq!(router.handle("/opds2/genre/999999").status, 404);
+
+    // the database error is logged, not sent to the client
+    let empty = OpdsApi::try_from(":memory:")?;
+    let response = Router::new(&empty).handle("/opds/authors");
+    assert_eq!(response.status, 500);
+    assert_eq!(response.body, b"Internal Server Error");
+    Ok(())
+}
+
+#[test]
+fn book() -> anyhow::Result<()> {
+    use crate::library::Library;
+    use std::io::Write;
+    use zip::{write::SimpleFileOptions, ZipWriter};
+
+    let dir = tempfile::tempdir()?;
+    let file = std::fs::File::create(dir.path().join("fb2-768381-769440.zip"))?;
+    let mut zip = ZipWriter::new(file);
+    for id in [768522, 768863] {
+        zip.start_file(format!("{id}.fb2"), SimpleFileOptions::default())?;
+        zip.write_all(b"<FictionBook/>")?;
+    }
+    zip.finish()?;
+
+    let api = OpdsApi::try_from(IMPORTED.as_str())?;
+    assert_eq!(
+        Router::new(&api).handle("/opds/book/768522/fb2").status,
+        404
+    );
+
+    let library = Library::new(dir.path())?;
+    let router = Router::new(&api).with_library(&library);
+    let response = router.handle("/opds/book/768522/fb2");
+    assert_eq!(response.status, 200);
+    assert_eq!(response.mime, crate::opds::FB2);
+    assert_eq!(response.filename.as_deref(), Some("768522.fb2"));
+    assert_eq!(response.book, Some((768522, 14)));
+    assert_eq!(response.content_length(), 14);
+    let mut body = Vec::new();
+    response.write_body(Some(&library), &mut body)?;
+    assert_eq!(body, b"<FictionBook/>");
+    assert!(response.write_body(None, &mut body).is_err());
+
+    let response = router.handle("/opds2/book/768522/fb2.zip");
+    assert_eq!(response.status, 200);
+    assert_eq!(response.mime, crate::opds::FB2_ZIP);
+    assert_eq!(response.filename.as_deref(), Some("768522.fb2.zip"));
+    assert!(response.body.starts_with(b"PK"));
+    assert_eq!(response.content_length(), response.body.len() as u64);
+
+    // the book without cover, deleted book and the book missing in the archive
+    assert_eq!(router.handle("/opds/book/768522/cover").status, 404);
+    assert_eq!(router.handle("/opds/book/768863/fb2").status, 404);
+    assert_eq!(router.handle("/opds/book/768523/fb2").status, 404);
+    assert_eq!(router.handle("/opds/book/abc/fb2").status, 400);
+    Ok(())
+}
+
+#[test]
+fn book_entries() -> anyhow::Result<()> {
+    use crate::library::Library;
+    use std::io::Write;
+    use zip::{write::SimpleFileOptions, ZipWriter};
+
+    let dir = tempfile::tempdir()?;
+    let file = std::fs::File::create(dir.path().join("fb2-768381-769440.zip"))?;
+    let mut zip = ZipWriter::new(file);
+    zip.start_file("768522.fb2", SimpleFileOptions::default())?;
+    zip.write_all(
+        r##"<FictionBook xmlns:l="http://www.w3.org/1999/xlink"><description><title-info>
+            <annotation><p>Змеи</p></annotation>
+            <coverpage><image l:href="#cover.png"/></coverpage>
+        </title-info></description>
+        <binary id="cover.png" content-type="image/png">iVBORw0KGgo=</binary></FictionBook>"##
+            .as_bytes(),
+    )?;
+    zip.finish()?;
+
+    let api = OpdsApi::try_from(DATABASE.as_str())?;
+    let feed = |router: &Router, target: &str| {
+        String::from_utf8(router.handle(target).body).expect("utf-8")
+    };
+
+    let router = Router::new(&api);
+    let atom = feed(&router, "/opds/title/Трон змей");
+    assert!(!atom.contains("http://opds-spec.org/image"));
+    assert!(!atom.contains("<summary"));
+
+    let library = Library::new(dir.path())?;
+    let router = Router::new(&api).with_library(&library);
+    let atom = feed(&router, "/opds/title/Трон змей");
+    assert!(!atom.contains("<summary"));
+    assert!(atom.contains(
+        r#"<link rel="http://opds-spec.org/image" href="/opds/book/768522/cover" type="image/jpeg"/>"#
+    ));
+
+    // the cover route parses the book, then the feeds show its details
+    assert_eq!(router.handle("/opds/book/768522/cover").mime, "image/png");
+    let atom = feed(&router, "/opds/title/Трон змей");
+    assert!(atom.contains(r#"<summary type="text">Змеи</summary>"#));
+    assert!(atom.contains(
+        r#"<link rel="http://opds-spec.org/image" href="/opds/book/768522/cover" type="image/png"/>"#
+    ));
+    let json = feed(&router, "/opds2/title/Трон змей");
+    assert!(json.contains(r#""description":"Змеи""#));
+    assert!(json.contains(r#""href":"/opds2/book/768522/cover","type":"image/png""#));
+
+    // the book missing in the archive
+    let title = "/opds/title/Основы человечности для чайников";
+    assert!(feed(&router, title).contains("http://opds-spec.org/image"));
+    assert_eq!(router.handle("/opds/book/768523/cover").status, 404);
+    let atom = feed(&router, title);
+    assert!(atom.contains("768523"));
+    assert!(!atom.contains("http://opds-spec.org/image"));
+    Ok(())
+}
+
+#[test]
+fn search() -> anyhow::Result<()> {
+    let api = OpdsApi::try_from(DATABASE.as_str())?;
+    let router = Router::new(&api);
+
+    let response = router.handle("/opds/opensearch.xml");
+    assert_eq!(response.status, 200);
+    assert_eq!(response.mime, crate::opds::OPENSEARCH);
+
+    let response = router.handle("/opds/search/%D0%9F%D0%B0%D0%B9%D0%BB");
+    assert_eq!(response.status, 200);
+    assert_eq!(response.mime, ACQUISITION);
+    let body = String::from_utf8(response.body)?;
+    assert!(body.contains("<title>Search: Пайл [1]</title>"));
+    assert!(body.contains("<title>Authors</title>"));
+    assert!(body.contains("<title>Говард Пайл</title>"));
+
+    // the hits are paged, the branches are only on the first page
+    let total = api.search_page("а", PageRequest::default())?.total;
+    let last = (total - 1) / crate::page::PAGE_SIZE;
+    assert!(last > 0);
+    let body = String::from_utf8(router.handle("/opds/search/%D0%B0").body)?;
+    assert!(body.contains(&format!("<title>Search: а [{total}]</title>")));
+    assert!(body.contains(r#"<link rel="next" href="/opds/search/%D0%B0?page=1""#));
+    assert!(body.contains("<title>Authors</title>"));
+    let body = String::from_utf8(router.handle("/opds/search/%D0%B0?page=1").body)?;
+    assert!(body.contains(r#"<link rel="previous" href="/opds/search/%D0%B0?page=0""#));
+    assert!(!body.contains("<title>Authors</title>"));
+    let json = String::from_utf8(router.handle("/opds2/search/%D0%B0").body)?;
+    assert!(json.contains(r#""rel":"next""#));
+    let target = format!("/opds2/search/%D0%B0?page={last}");
+    let json = String::from_utf8(router.handle(&target).body)?;
+    assert!(!json.contains(r#""rel":"next""#));
+    Ok(())
+}
+
+#[test]
+fn paginated() -> anyhow::Result<()> {
+    let api = OpdsApi::try_from(DATABASE.as_str())?;
+    let router = Router::new(&api);
+    let total = api
+        .books_by_genre_id_and_date(13, String::from("%"))?
+        .len() as u32;
+    let last = (total - 1) / crate::page::PAGE_SIZE;
+    assert!(last > 0);
+
+    let body = String::from_utf8(router.handle("/opds/genre/13/books").body)?;
+    assert!(body.contains(r#"<link rel="first" href="/opds/genre/13/books?page=0""#));
+    assert!(body.contains(r#"<link rel="next" href="/opds/genre/13/books?page=1""#));
+    assert!(!body.contains(r#"rel="previous""#));
+    let last_link = format!(r#"<link rel="last" href="/opds/genre/13/books?page={last}""#);
+    assert!(body.contains(&last_link));
+
+    let target = format!("/opds/genre/13/books?page={last}");
+    let body = String::from_utf8(router.handle(&target).body)?;
+    let previous = format!(r#"<link rel="previous" href="/opds/genre/13/books?page={}""#, last - 1);
+    assert!(body.contains(&previous));
+    assert!(!body.contains(r#"rel="next""#));
+
+    let body = String::from_utf8(router.handle("/opds/author/43/2/184").body)?;
+    assert!(!body.contains(r#"rel="first""#));
+
+    assert_eq!(router.handle("/opds/genre/13/books?page=x").status, 400);
+    Ok(())
+}
+
+#[test]
+fn new_arrivals() -> anyhow::Result<()> {
+    let api = OpdsApi::try_from(DATABASE.as_str())?;
+    let router = Router::new(&api);
+
+    let response = router.handle("/opds/new");
+    assert_eq!(response.status, 200);
+    assert_eq!(response.mime, ACQUISITION);
+
+    let response = router.handle("/opds2/new/2024-06-30");
+    assert_eq!(response.status, 200);
+    assert!(String::from_utf8(response.body)?.contains(r#""identifier":"urn:opds_api:book:769438""#));
+
+    assert_eq!(router.handle("/opds/new/2024-06-31").status, 404);
+    assert_eq!(router.handle("/opds/new/yesterday").status, 404);
+    Ok(())
+}
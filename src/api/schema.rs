@@ -0,0 +1,101 @@
+use super::*;
+
+use crate::schema::{self, Status};
+
+fn copy_of<P: AsRef<std::path::Path>>(dir: &tempfile::TempDir, db: P) -> anyhow::Result<String> {
+    let path = dir.path().join("library.db");
+    std::fs::copy(db, &path)?;
+    Ok(path.to_string_lossy().into_owned())
+}
+
+fn copy(dir: &tempfile::TempDir) -> anyhow::Result<String> {
+    copy_of(dir, &*SAMPLE)
+}
+
+#[test]
+fn current() -> anyhow::Result<()> {
+    let api = OpdsApi::try_from(DATABASE.as_str())?;
+    assert_eq!(api.schema_version()?, schema::VERSION);
+    assert_eq!(api.schema_status()?, Status::Current);
+    Ok(())
+}
+
+#[test]
+fn migrate_legacy() -> anyhow::Result<()> {
+    let dir = tempfile::tempdir()?;
+    let path = copy_of(&dir, "data/fb2-768381-769440.db")?;
+    {
+        let conn = rusqlite::Connection::open(&path)?;
+        assert_eq!(schema::status(&conn)?, Status::Outdated(0));
+    }
+
+    let readonly = format!("file:{path}?mode=ro");
+    assert!(matches!(
+        OpdsApi::try_from(readonly.as_str()),
+        Err(Error::Schema { version: 0, .. })
+    ));
+    assert!(matches!(
+        OpdsApi::try_from(path.as_str()),
+        Err(Error::Schema { version: 0, .. })
+    ));
+    assert_eq!(schema::version(&rusqlite::Connection::open(&path)?)?, 0);
+
+    let api = OpdsApi::open_migrated(path.as_str())?;
+    assert_eq!(api.schema_status()?, Status::Current);
+    assert!(api.imports()?.is_empty());
+    assert!(!api.search("дракон", 10)?.is_empty());
+    assert_eq!(api.validate()?, schema::VERSION);
+    Ok(())
+}
+
+#[test]
+fn outdated_refused() -> anyhow::Result<()> {
+    let legacy = "file:data/fb2-768381-769440.db?mode=ro";
+    let refused = |result: Result<()>| match result {
+        Err(e @ Error::Schema { version: 0, .. }) => e.to_string().contains("open_migrated"),
+        _ => false,
+    };
+    assert!(refused(OpdsApi::try_from(legacy).map(|_| ())));
+    assert!(refused(OpdsPool::open(legacy, 1).map(|_| ())));
+    Ok(())
+}
+
+#[test]
+fn unknown_compatible() -> anyhow::Result<()> {
+    let dir = tempfile::tempdir()?;
+    let path = copy(&dir)?;
+    let version = schema::VERSION + 1;
+    rusqlite::Connection::open(&path)?.pragma_update(None, "user_version", version)?;
+
+    let mut api = OpdsApi::try_from(path.as_str())?;
+    assert_eq!(api.schema_status()?, Status::Unknown(version));
+    assert!(api.book_by_id(768866)?.is_some());
+    assert!(matches!(api.migrate(), Err(Error::UnsupportedVersion(v)) if v == version));
+    assert!(matches!(
+        api.import_inp(INP),
+        Err(Error::UnsupportedVersion(_))
+    ));
+    assert_eq!(api.schema_version()?, version);
+    Ok(())
+}
+
+#[test]
+fn unknown_refused() -> anyhow::Result<()> {
+    let dir = tempfile::tempdir()?;
+    let path = copy(&dir)?;
+    {
+        let conn = rusqlite::Connection::open(&path)?;
+        conn.execute_batch("ALTER TABLE books RENAME COLUMN book_size TO size")?;
+        conn.pragma_update(None, "user_version", schema::VERSION + 1)?;
+    }
+
+    assert!(matches!(
+        OpdsApi::try_from(path.as_str()),
+        Err(Error::Schema { .. })
+    ));
+    assert!(matches!(
+        OpdsPool::open(format!("file:{path}?mode=ro").as_str(), 1),
+        Err(Error::Schema { .. })
+    ));
+    Ok(())
+}
@@ -0,0 +1,69 @@
+use super::*;
+
+fn names(found: &[Found]) -> Vec<String> {
+    found
+        .iter()
+        .map(|found| match found {
+            Found::Author(author) => format!("author {author}"),
+            Found::Serie(serie) => format!("serie {}", serie.name),
+            Found::Book(book) => format!("book {} {}", book.id, book.name),
+        })
+        .collect()
+}
+
+#[test]
+fn search_book_by_word() -> anyhow::Result<()> {
+    let api = OpdsApi::try_from(DATABASE.as_str())?;
+
+    let expected = vec!["book 768409 Рыцари, закованные в сталь"];
+    assert_eq!(names(&api.search("закованные", 10)?), expected);
+    assert_eq!(names(&api.search("Рыцари, заков", 10)?), expected);
+    Ok(())
+}
+
+#[test]
+fn search_mixed() -> anyhow::Result<()> {
+    let api = OpdsApi::try_from(IMPORTED.as_str())?;
+
+    assert_eq!(
+        names(&api.search("дракон", 3)?),
+        vec![
+            "serie Драконьи Острова",
+            "book 768382 Алиана, спасительница драконов",
+            "book 768918 Драконоборцы. 100 научных сказок",
+        ]
+    );
+    assert_eq!(
+        names(&api.search("злотников", 10)?),
+        vec!["author Роман Валерьевич Злотников"]
+    );
+    Ok(())
+}
+
+#[test]
+fn search_limit() -> anyhow::Result<()> {
+    let api = OpdsApi::try_from(DATABASE.as_str())?;
+
+    let found = names(&api.search("антология", 3)?);
+    assert_eq!(found.len(), 3);
+    let unique = found.iter().collect::<std::collections::HashSet<_>>();
+    assert_eq!(unique.len(), found.len());
+
+    let series = api
+        .search("антология", 100)?
+        .into_iter()
+        .filter(|found| matches!(found, Found::Serie(_)))
+        .count();
+    assert_eq!(series, 5);
+    Ok(())
+}
+
+#[test]
+fn search_nothing() -> anyhow::Result<()> {
+    let api = OpdsApi::try_from(DATABASE.as_str())?;
+
+    assert!(api.search("", 10)?.is_empty());
+    assert!(api.search("\"*()", 10)?.is_empty());
+    assert!(api.search("несуществующее", 10)?.is_empty());
+    Ok(())
+}
@@ -2,7 +2,7 @@ use super::*;
 
 #[test]
 fn series_next_char_by_prefix() -> anyhow::Result<()> {
-    let api = OpdsApi::try_from(DATABASE)?;
+    let api = OpdsApi::try_from(DATABASE.as_str())?;
     let result = api.series_next_char_by_prefix(&String::from("Го"))?;
 
     assert_eq!(result, vec!["Гон", "Гор", "Гос"]);
@@ -11,7 +11,7 @@ fn series_next_char_by_prefix() -> anyhow::Result<()> {
 
 #[test]
 fn search_series_by_prefix() -> anyhow::Result<()> {
-    let api = OpdsApi::try_from(DATABASE)?;
+    let api = OpdsApi::try_from(DATABASE.as_str())?;
     let result = api.search_series_by_prefix(&String::from("Авр"))?;
 
     assert_eq!(result, (vec![String::from("Аврора [Кауфман]")], vec![]));
@@ -20,7 +20,7 @@ fn search_series_by_prefix() -> anyhow::Result<()> {
 
 #[test]
 fn series_by_ids() -> anyhow::Result<()> {
-    let api = OpdsApi::try_from(DATABASE)?;
+    let api = OpdsApi::try_from(DATABASE.as_str())?;
 
     let strings = api
         .series_by_ids(vec![42, 44, 2])?
@@ -45,7 +45,7 @@ fn series_by_ids() -> anyhow::Result<()> {
 
 #[test]
 fn series_by_serie_name() -> anyhow::Result<()> {
-    let api = OpdsApi::try_from(DATABASE)?;
+    let api = OpdsApi::try_from(DATABASE.as_str())?;
     let strings = api
         .series_by_serie_name(&String::from("Кровь на воздух"))?
         .into_iter()
@@ -60,7 +60,7 @@ fn series_by_serie_name() -> anyhow::Result<()> {
 
 #[test]
 fn series_by_genre_id() -> anyhow::Result<()> {
-    let api = OpdsApi::try_from(DATABASE)?;
+    let api = OpdsApi::try_from(DATABASE.as_str())?;
     let strings = api
         .series_by_genre_id(24)?
         .into_iter()
@@ -78,7 +78,7 @@ fn series_by_genre_id() -> anyhow::Result<()> {
 
 #[test]
 fn series_by_author_ids() -> anyhow::Result<()> {
-    let api = OpdsApi::try_from(DATABASE)?;
+    let api = OpdsApi::try_from(DATABASE.as_str())?;
     let strings = api
         .series_by_author_ids(50, 42, 281)?
         .into_iter()
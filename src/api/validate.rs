@@ -0,0 +1,47 @@
+use super::*;
+
+#[test]
+fn validate() -> anyhow::Result<()> {
+    let api = OpdsApi::try_from(DATABASE.as_str())?;
+    assert!(!api.is_empty()?);
+    assert_eq!(api.validate()?, api.schema_version()?);
+    Ok(())
+}
+
+#[test]
+fn validate_empty() -> anyhow::Result<()> {
+    let api = OpdsApi::try_from(":memory:")?;
+    assert!(api.is_empty()?);
+
+    match api.validate() {
+        Err(Error::Schema { failures, .. }) => {
+            assert_eq!(failures.len(), queries::QUERIES.len());
+            assert!(failures.contains(&String::from("LatestDate: no such table: dates")));
+        }
+        other => panic!("Unexpected {other:?}"),
+    }
+    Ok(())
+}
+
+#[test]
+fn validate_renamed_column() -> anyhow::Result<()> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("renamed.db");
+    std::fs::copy(&*SAMPLE, &path)?;
+
+    let api = OpdsApi::try_from(path.to_string_lossy().as_ref())?;
+    api.conn
+        .execute_batch("ALTER TABLE langs RENAME COLUMN value TO code")?;
+
+    match api.validate() {
+        Err(e @ Error::Schema { .. }) => {
+            let message = e.to_string();
+            assert!(message.contains("schema version 5"), "{message}");
+            assert!(message.contains("Languages: no such column: langs.value"));
+            assert!(message.contains("BookById: no such column: langs.value"));
+            assert!(!message.contains("MetaGenres"));
+        }
+        other => panic!("Unexpected {other:?}"),
+    }
+    Ok(())
+}
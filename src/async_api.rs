@@ -0,0 +1,235 @@
+use log::debug;
+use rusqlite::InterruptHandle;
+use tokio::sync::Semaphore;
+
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex, PoisonError,
+    },
+};
+
+use crate::{
+    importer::Import, Author, AuthorInfo, Book, Date, Error, Found, Genre, OpdsApi, OpdsPool, Page,
+    PageRequest, Result, Serie, Value,
+};
+
+/// Async facade of the OpdsPool for the tokio based servers
+///
+/// Every call runs on the tokio blocking pool with its own connection.
+/// No more calls than connections run at once, the rest wait without blocking
+/// the runtime, and calls over the queue limit fail with [Error::Busy].
+/// Dropping the future cancels the call: the waiting call is removed
+/// from the queue and the running SQL statement is interrupted.
+#[derive(Debug, Clone)]
+pub struct AsyncOpdsApi {
+    pool: Arc<OpdsPool>,
+    permits: Arc<Semaphore>,
+    pending: Arc<AtomicUsize>,
+    queue_limit: usize,
+    /// Language codes of the calls, the pool default if None
+    languages: Option<Arc<Vec<String>>>,
+}
+impl AsyncOpdsApi {
+    pub fn new(pool: OpdsPool) -> Self {
+        Self {
+            permits: Arc::new(Semaphore::new(pool.size())),
+            pool: Arc::new(pool),
+            pending: Arc::default(),
+            queue_limit: usize::MAX,
+            languages: None,
+        }
+    }
+
+    /// Opens the pool of `size` read-only connections to the database
+    pub fn open(database: &str, size: usize) -> Result<Self> {
+        OpdsPool::open(database, size).map(Self::new)
+    }
+
+    /// Fails the calls with [Error::Busy] when `limit` calls are already running or waiting
+    pub fn with_queue_limit(mut self, limit: usize) -> Self {
+        self.queue_limit = limit;
+        self
+    }
+
+    /// Restricts the calls of this handle to the books in the languages, e.g. `["uk", "en"]`
+    ///
+    /// Other clones of the handle keep their languages, see [OpdsApi::set_languages]
+    pub fn with_languages(mut self, codes: Vec<String>) -> Self {
+        self.languages = Some(Arc::new(codes));
+        self
+    }
+
+    /// Number of the running and waiting calls
+    pub fn pending(&self) -> usize {
+        self.pending.load(Ordering::SeqCst)
+    }
+
+    /// Runs the closure with the pooled connection on the blocking thread
+    pub async fn call<T, F>(&self, f: F) -> Result<T>
+    where
+        F: FnOnce(&OpdsApi) -> Result<T> + Send + 'static,
+        T: Send + 'static,
+    {
+        let _pending = Pending::enter(&self.pending, self.queue_limit)?;
+        let permit = Arc::clone(&self.permits)
+            .acquire_owned()
+            .await
+            .map_err(|_| Error::Cancelled)?;
+
+        let pool = Arc::clone(&self.pool);
+        let languages = self.languages.clone();
+        let guard = Cancel::default();
+        let state = Arc::clone(&guard.0);
+        let task = tokio::task::spawn_blocking(move || {
+            let _permit = permit;
+            let api = pool.get();
+            match &mut *state.lock().unwrap_or_else(PoisonError::into_inner) {
+                State::Cancelled => return Err(Error::Cancelled),
+                state => *state = State::Running(api.conn.get_interrupt_handle()),
+            }
+            let result = languages
+                .map_or(Ok(()), |codes| api.set_languages(codes.as_slice()))
+                .and_then(|_| f(&api));
+            *state.lock().unwrap_or_else(PoisonError::into_inner) = State::Done;
+            result
+        });
+        let result = task.await.unwrap_or_else(|e| match e.try_into_panic() {
+            Ok(panic) => std::panic::resume_unwind(panic),
+            Err(_) => Err(Error::Cancelled),
+        });
+        drop(guard);
+        result
+    }
+}
+
+/// Defines async variants of the OpdsApi methods
+///
+/// Arguments are passed to the method as is unless the list of
+/// the passed expressions follows the signature
+macro_rules! delegate {
+    (@call $api:ident $name:ident ($($arg:ident),*)) => {
+        $api.$name($($arg),*)
+    };
+    (@call $api:ident $name:ident ($($arg:ident),*) ($($pass:expr),*)) => {
+        $api.$name($($pass),*)
+    };
+    ($($name:ident($($arg:ident: $ty:ty),*) -> $ret:ty $(=> ($($pass:expr),*))?;)*) => {
+        impl AsyncOpdsApi {
+            $(
+                #[doc = concat!("Async variant of [OpdsApi::", stringify!($name), "]")]
+                pub async fn $name(&self, $($arg: $ty),*) -> Result<$ret> {
+                    debug!(concat!(stringify!($name), " <- {:?}"), ($(&$arg,)*));
+                    self.call(move |api| {
+                        delegate!(@call api $name ($($arg),*) $(($($pass),*))?)
+                    })
+                    .await
+                }
+            )*
+        }
+    };
+}
+
+delegate! {
+    languages() -> Vec<(String, u32)>;
+    imports() -> Vec<Import>;
+    is_readonly() -> bool;
+    search(query: String, limit: u32) -> Vec<Found> => (&query, limit);
+    search_page(query: String, page: PageRequest) -> Page<Found> => (&query, page);
+    search_authors_by_prefix(prefix: String) -> (Vec<String>, Vec<String>) => (&prefix);
+    authors_next_char_by_prefix(prefix: String) -> Vec<String> => (&prefix);
+    series_next_char_by_prefix(prefix: String) -> Vec<String> => (&prefix);
+    books_next_char_by_prefix(prefix: String) -> Vec<String> => (&prefix);
+    search_series_by_prefix(prefix: String) -> (Vec<String>, Vec<String>) => (&prefix);
+    search_books_by_prefix(prefix: String) -> (Vec<String>, Vec<String>) => (&prefix);
+    authors_by_last_name(name: String) -> Vec<AuthorInfo> => (&name);
+    authors_by_last_name_page(name: String, page: PageRequest) -> Page<AuthorInfo> => (&name, page);
+    authors_by_genre_id(gid: u32) -> Vec<AuthorInfo>;
+    authors_by_genre_id_page(gid: u32, page: PageRequest) -> Page<AuthorInfo>;
+    authors_by_books_ids(ids: Vec<u32>) -> Vec<Author>;
+    series_by_ids(ids: Vec<u32>) -> Vec<Serie>;
+    series_by_serie_name(name: String) -> Vec<Serie> => (&name);
+    series_by_serie_name_page(name: String, page: PageRequest) -> Page<Serie> => (&name, page);
+    series_by_genre_id(gid: u32) -> Vec<Serie>;
+    series_by_genre_id_page(gid: u32, page: PageRequest) -> Page<Serie>;
+    series_by_author_ids(fid: u32, mid: u32, lid: u32) -> Vec<Serie>;
+    series_by_author_ids_page(fid: u32, mid: u32, lid: u32, page: PageRequest) -> Page<Serie>;
+    author_by_ids(fid: u32, mid: u32, lid: u32) -> Option<Author>;
+    book_by_id(bid: u32) -> Option<Book>;
+    books_by_author_ids(fid: u32, mid: u32, lid: u32) -> Vec<Book>;
+    books_by_author_ids_page(fid: u32, mid: u32, lid: u32, page: PageRequest) -> Page<Book>;
+    deleted_books_by_author_ids(fid: u32, mid: u32, lid: u32) -> Vec<Book>;
+    deleted_books_by_author_ids_page(fid: u32, mid: u32, lid: u32, page: PageRequest) -> Page<Book>;
+    deleted_books_count_by_author_ids(fid: u32, mid: u32, lid: u32) -> u32;
+    books_by_author_ids_and_serie_id(fid: u32, mid: u32, lid: u32, sid: u32) -> Vec<Book>;
+    books_by_author_ids_and_serie_id_page(
+        fid: u32, mid: u32, lid: u32, sid: u32, page: PageRequest
+    ) -> Page<Book>;
+    books_by_author_ids_without_serie(fid: u32, mid: u32, lid: u32) -> Vec<Book>;
+    books_by_author_ids_without_serie_page(
+        fid: u32, mid: u32, lid: u32, page: PageRequest
+    ) -> Page<Book>;
+    books_by_serie_id(sid: u32) -> Vec<Book>;
+    books_by_serie_id_page(sid: u32, page: PageRequest) -> Page<Book>;
+    deleted_books_by_serie_id(sid: u32) -> Vec<Book>;
+    deleted_books_by_serie_id_page(sid: u32, page: PageRequest) -> Page<Book>;
+    deleted_books_count_by_serie_id(sid: u32) -> u32;
+    books_by_genre_id_and_date(gid: u32, date: String) -> Vec<Book>;
+    books_by_genre_id_and_date_page(gid: u32, date: String, page: PageRequest) -> Page<Book>;
+    books_added_between(from: Date, to: Date) -> Vec<Book>;
+    books_added_between_page(from: Date, to: Date, page: PageRequest) -> Page<Book>;
+    last_days(days: u32) -> Option<(Date, Date)>;
+    books_added_in_last_days(days: u32) -> Vec<Book>;
+    books_added_in_last_days_page(days: u32, page: PageRequest) -> Page<Book>;
+    books_by_book_title(name: String) -> Vec<Book> => (&name);
+    books_by_book_title_page(name: String, page: PageRequest) -> Page<Book> => (&name, page);
+    meta_genres() -> Vec<String>;
+    genres_by_meta(meta: String) -> Vec<Value> => (&meta);
+    genre_by_id(gid: u32) -> Option<Value>;
+    genres_by_book_id(id: u32) -> Vec<Genre>;
+    genres_by_books_ids(ids: Vec<u32>) -> HashMap<u32, Vec<Genre>>;
+    latest_date() -> Option<String>;
+}
+
+/// Counts the call as pending while alive
+struct Pending<'a>(&'a AtomicUsize);
+impl<'a> Pending<'a> {
+    fn enter(pending: &'a AtomicUsize, limit: usize) -> Result<Self> {
+        pending
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |count| {
+                (count < limit).then_some(count + 1)
+            })
+            .map_err(|_| Error::Busy)?;
+        Ok(Self(pending))
+    }
+}
+impl Drop for Pending<'_> {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+#[derive(Default)]
+enum State {
+    #[default]
+    Waiting,
+    Running(InterruptHandle),
+    Cancelled,
+    Done,
+}
+
+/// Interrupts the running call when the future is dropped
+#[derive(Default)]
+struct Cancel(Arc<Mutex<State>>);
+impl Drop for Cancel {
+    fn drop(&mut self) {
+        let mut state = self.0.lock().unwrap_or_else(PoisonError::into_inner);
+        if let State::Running(handle) = &*state {
+            handle.interrupt();
+        }
+        if !matches!(*state, State::Done) {
+            *state = State::Cancelled;
+        }
+    }
+}
@@ -2,6 +2,7 @@ use crate::value::Value;
 use std::fmt;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Author {
     pub first_name: Value,
     pub middle_name: Value,
@@ -21,7 +22,6 @@ impl fmt::Display for Author {
         let out = std::iter::once(self.first_name.value.trim())
             .chain(std::iter::once(self.middle_name.value.trim()))
             .chain(std::iter::once(self.last_name.value.trim()))
-            .into_iter()
             .filter(|s| !s.is_empty())
             .collect::<Vec<&str>>()
             .join(" ");
@@ -29,10 +29,56 @@ impl fmt::Display for Author {
     }
 }
 
+/// Author with the number of the available books and series
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AuthorInfo {
+    pub author: Author,
+    pub books: u32,
+    pub series: u32,
+}
+impl AuthorInfo {
+    pub fn new(author: Author, books: u32, series: u32) -> Self {
+        Self {
+            author,
+            books,
+            series,
+        }
+    }
+
+    /// Returns the summary like "12 books in 3 series"
+    pub fn summary(&self) -> String {
+        let books = match self.books {
+            1 => String::from("1 book"),
+            n => format!("{n} books"),
+        };
+        if self.series > 0 {
+            format!("{books} in {} series", self.series)
+        } else {
+            books
+        }
+    }
+}
+impl fmt::Display for AuthorInfo {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(formatter, "{} ({})", self.author, self.summary())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn info_fmt() {
+        let author = Author::new(Value::new(1, "A"), Value::new(2, ""), Value::new(3, "C"));
+        assert_eq!(
+            "A C (12 books in 3 series)",
+            format!("{}", AuthorInfo::new(author.clone(), 12, 3))
+        );
+        assert_eq!("A C (1 book)", format!("{}", AuthorInfo::new(author, 1, 0)));
+    }
+
     #[test]
     fn fmt() {
         assert_eq!(
@@ -0,0 +1,206 @@
+use log::{error, info};
+use opds_api::{
+    library::Library,
+    router::{Response, Router},
+    OpdsPool,
+};
+
+use std::{
+    io::{self, BufRead, BufReader, Read, Write},
+    net::{TcpListener, TcpStream},
+    sync::atomic::{AtomicUsize, Ordering},
+    thread,
+    time::Duration,
+};
+
+const USAGE: &str = "Usage: opds_server [--migrate] <database> [address] [library]
+
+  --migrate  upgrade the database schema before serving, the file must be writable";
+const ADDRESS: &str = "127.0.0.1:8080";
+const POOL_SIZE: usize = 4;
+/// Connections served at once, the rest are answered with 503
+const MAX_WORKERS: usize = 32;
+/// Time to wait for the request and for the client to accept the response
+const TIMEOUT: Duration = Duration::from_secs(10);
+/// Longest request line or header line in bytes
+const MAX_LINE: usize = 8 * 1024;
+/// Most header lines of the request
+const MAX_HEADERS: usize = 64;
+
+fn main() -> anyhow::Result<()> {
+    env_logger::init();
+
+    let (flags, args): (Vec<_>, Vec<_>) =
+        std::env::args().skip(1).partition(|arg| arg.starts_with("--"));
+    let migrate = match flags.as_slice() {
+        [] => false,
+        [flag] if flag == "--migrate" => true,
+        _ => anyhow::bail!(USAGE),
+    };
+    let mut args = args.into_iter();
+    let database = args.next().ok_or_else(|| anyhow::anyhow!(USAGE))?;
+    let address = args.next().unwrap_or_else(|| String::from(ADDRESS));
+    let library = args
+        .next()
+        .map(|library| {
+            info!("Serving books from {library}");
+            Library::new(library)
+        })
+        .transpose()?;
+
+    let pool = if migrate {
+        info!("Upgrading the schema of {database}");
+        OpdsPool::open_migrated(&database, POOL_SIZE)?
+    } else {
+        OpdsPool::open_path(&database, POOL_SIZE)?
+    };
+    let version = pool.get().validate()?;
+    info!("Schema version {version} is valid");
+    let listener = TcpListener::bind(&address)?;
+    info!("Serving {database} on http://{address}/opds");
+
+    let workers = AtomicUsize::new(0);
+    thread::scope(|scope| {
+        for stream in listener.incoming() {
+            match stream {
+                Ok(mut stream) => {
+                    if workers.fetch_add(1, Ordering::SeqCst) >= MAX_WORKERS {
+                        workers.fetch_sub(1, Ordering::SeqCst);
+                        let response = Response::text(503, "Service Unavailable");
+                        if let Err(e) = respond(&mut stream, &response, None, true) {
+                            error!("{e}");
+                        }
+                        continue;
+                    }
+                    let (pool, library, workers) = (&pool, library.as_ref(), &workers);
+                    scope.spawn(move || {
+                        if let Err(e) = serve(pool, library, stream) {
+                            error!("{e}");
+                        }
+                        workers.fetch_sub(1, Ordering::SeqCst);
+                    });
+                }
+                Err(e) => error!("{e}"),
+            }
+        }
+    });
+    Ok(())
+}
+
+/// Reads the request, then routes it with the pooled connection and writes the response
+fn serve(pool: &OpdsPool, library: Option<&Library>, mut stream: TcpStream) -> anyhow::Result<()> {
+    stream.set_read_timeout(Some(TIMEOUT))?;
+    stream.set_write_timeout(Some(TIMEOUT))?;
+
+    let (method, target) = match read_request(&mut BufReader::new(&stream)) {
+        Ok(request) => request,
+        Err(e) if e.kind() == io::ErrorKind::InvalidData => {
+            let response = Response::text(400, "Bad Request");
+            respond(&mut stream, &response, None, true)?;
+            return Err(e.into());
+        }
+        Err(e) => return Err(e.into()),
+    };
+    info!("{method} {target}");
+
+    let response = match method.as_str() {
+        "GET" | "HEAD" => {
+            let api = pool.get();
+            let mut router = Router::new(&api);
+            if let Some(library) = library {
+                router = router.with_library(library);
+            }
+            router.handle(&target)
+        }
+        _ => Response::text(405, "Method Not Allowed"),
+    };
+    respond(&mut stream, &response, library, method != "HEAD")
+}
+
+/// Reads the request line and skips the headers, returns the method and the target
+fn read_request<R: BufRead>(reader: &mut R) -> io::Result<(String, String)> {
+    let request = read_line(reader)?;
+    for _ in 0..MAX_HEADERS {
+        if read_line(reader)?.trim().is_empty() {
+            let mut parts = request.split_whitespace();
+            let method = parts.next().unwrap_or_default().to_owned();
+            let target = parts.next().unwrap_or_default().to_owned();
+            return Ok((method, target));
+        }
+    }
+    Err(io::Error::new(
+        io::ErrorKind::InvalidData,
+        "too many headers",
+    ))
+}
+
+/// Reads the line of at most MAX_LINE bytes
+fn read_line<R: BufRead>(reader: &mut R) -> io::Result<String> {
+    let mut line = String::new();
+    reader.by_ref().take(MAX_LINE as u64).read_line(&mut line)?;
+    if line.ends_with('\n') {
+        Ok(line)
+    } else if line.len() < MAX_LINE {
+        Err(io::Error::new(
+            io::ErrorKind::UnexpectedEof,
+            "incomplete request",
+        ))
+    } else {
+        Err(io::Error::new(io::ErrorKind::InvalidData, "too long line"))
+    }
+}
+
+fn respond(
+    stream: &mut TcpStream,
+    response: &Response,
+    library: Option<&Library>,
+    body: bool,
+) -> anyhow::Result<()> {
+    write!(
+        stream,
+        "HTTP/1.1 {} {}\r\nContent-Type: {}\r\nContent-Length: {}\r\n",
+        response.status,
+        response.reason(),
+        response.mime,
+        response.content_length()
+    )?;
+    if let Some(filename) = &response.filename {
+        write!(
+            stream,
+            "Content-Disposition: attachment; filename=\"{filename}\"\r\n"
+        )?;
+    }
+    write!(stream, "Connection: close\r\n\r\n")?;
+    if body {
+        response.write_body(library, stream)?;
+    }
+    stream.flush()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn request() -> io::Result<()> {
+        let mut reader = "GET /opds HTTP/1.1\r\nHost: localhost\r\n\r\n".as_bytes();
+        let request = read_request(&mut reader)?;
+        assert_eq!(request, (String::from("GET"), String::from("/opds")));
+        Ok(())
+    }
+
+    #[test]
+    fn limits() {
+        let kind = |request: String| read_request(&mut request.as_bytes()).map_err(|e| e.kind());
+
+        let long = format!("GET /{} HTTP/1.1\r\n\r\n", "a".repeat(MAX_LINE));
+        assert_eq!(kind(long), Err(io::ErrorKind::InvalidData));
+
+        let headers = format!("GET / HTTP/1.1\r\n{}\r\n", "X: y\r\n".repeat(MAX_HEADERS));
+        assert_eq!(kind(headers), Err(io::ErrorKind::InvalidData));
+
+        let truncated = String::from("GET / HTTP/1.1\r\nHost");
+        assert_eq!(kind(truncated), Err(io::ErrorKind::UnexpectedEof));
+    }
+}
@@ -3,14 +3,17 @@ use std::fmt;
 use crate::Author;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Book {
     pub id: u32,
     pub name: String,
     pub sid: Option<u32>,
     pub idx: Option<u32>,
-    pub author: Author,
+    pub authors: Vec<Author>,
     pub size: u32,
     pub added: String,
+    /// Language code of the book, e.g. "ru"
+    pub lang: String,
 }
 impl Book {
     pub fn new<T: Into<String>>(
@@ -18,7 +21,7 @@ impl Book {
         name: T,
         sid: Option<u32>,
         idx: Option<u32>,
-        author: Author,
+        authors: Vec<Author>,
         size: u32,
         added: T,
     ) -> Self {
@@ -26,25 +29,37 @@ impl Book {
             id,
             sid,
             idx,
-            author,
+            authors,
             name: name.into(),
             size,
             added: added.into(),
+            lang: String::new(),
         }
     }
+
+    pub fn with_lang<T: Into<String>>(mut self, lang: T) -> Self {
+        self.lang = lang.into();
+        self
+    }
 }
 impl fmt::Display for Book {
     fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
         let size = format_size(self.size);
+        let authors = self
+            .authors
+            .iter()
+            .map(|author| author.to_string())
+            .collect::<Vec<_>>()
+            .join(", ");
         if let Some(idx) = self.idx {
-            write!(fmt, "{idx} {} - {} ({}) [{size}]", self.name, self.author, self.added)
+            write!(fmt, "{idx} {} - {authors} ({}) [{size}]", self.name, self.added)
         } else {
-            write!(fmt, "{} - {} ({}) [{size}]", self.name, self.author, self.added)
+            write!(fmt, "{} - {authors} ({}) [{size}]", self.name, self.added)
         }
     }
 }
 
-fn format_size(size: u32) -> String {
+pub(crate) fn format_size(size: u32) -> String {
     const KB: f64 = 1024.0;
     const MB: f64 = 1024.0 * KB;
 
@@ -76,11 +91,21 @@ mod tests {
             "1 T - F M L (2024-10-10) [42 B]",
             format!(
                 "{}",
-                &Book::new(1, "T", Some(42), Some(1), Author {
+                &Book::new(1, "T", Some(42), Some(1), vec![Author {
                     first_name: Value::new(1, "F"),
                     middle_name: Value::new(2, "M"),
                     last_name: Value::new(3, "L"),
-                }, 42, "2024-10-10")
+                }], 42, "2024-10-10")
+            )
+        );
+        assert_eq!(
+            "T - F L, A B (2024-10-10) [42 B]",
+            format!(
+                "{}",
+                &Book::new(1, "T", None, None, vec![
+                    Author::new(Value::new(1, "F"), Value::new(2, ""), Value::new(3, "L")),
+                    Author::new(Value::new(4, "A"), Value::new(2, ""), Value::new(5, "B")),
+                ], 42, "2024-10-10")
             )
         );
     }
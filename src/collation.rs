@@ -1,12 +1,15 @@
 use std::cmp::*;
 use std::collections::HashMap;
+use std::iter::Peekable;
 
 lazy_static::lazy_static! {
     static ref ORDER: HashMap<char, usize> = vec![
-        'А', 'Б', 'В', 'Г', 'Д', 'Е', 'Ё', 'Ж', 'З', 'И', 'Й', 'К', 'Л', 'М', 'Н', 'О', 'П',
-        'Р', 'С', 'Т', 'У', 'Ф', 'Х', 'Ц', 'Ч', 'Ш', 'Щ', 'Ъ', 'Ы', 'Ь', 'Э', 'Ю', 'Я',
-        'а', 'б', 'в', 'г', 'д', 'е', 'ё', 'ж', 'з', 'и', 'й', 'к', 'л', 'м', 'н', 'о', 'п',
-        'р', 'с', 'т', 'у', 'ф', 'х', 'ц', 'ч', 'ш', 'щ', 'ъ', 'ы', 'ь', 'э', 'ю', 'я',
+        'А', 'Б', 'В', 'Г', 'Ґ', 'Д', 'Е', 'Ё', 'Є', 'Ж', 'З', 'И', 'І', 'Ї', 'Й', 'К', 'Л',
+        'М', 'Н', 'О', 'П', 'Р', 'С', 'Т', 'У', 'Ў', 'Ф', 'Х', 'Ц', 'Ч', 'Ш', 'Щ', 'Ъ', 'Ы',
+        'Ь', 'Э', 'Ю', 'Я',
+        'а', 'б', 'в', 'г', 'ґ', 'д', 'е', 'ё', 'є', 'ж', 'з', 'и', 'і', 'ї', 'й', 'к', 'л',
+        'м', 'н', 'о', 'п', 'р', 'с', 'т', 'у', 'ў', 'ф', 'х', 'ц', 'ч', 'ш', 'щ', 'ъ', 'ы',
+        'ь', 'э', 'ю', 'я',
         'A', 'B', 'C', 'D', 'E', 'F', 'G', 'H', 'I', 'J', 'K', 'L', 'M',
         'N', 'O', 'P', 'Q', 'R', 'S', 'T', 'U', 'V', 'W', 'X', 'Y', 'Z',
         'a', 'b', 'c', 'd', 'e', 'f', 'g', 'h', 'i', 'j', 'k', 'l', 'm',
@@ -15,27 +18,258 @@ lazy_static::lazy_static! {
         '!', '"', '#', '$', '%', '&', '\'', '(', ')', '*', '+', ',', '-', '.', '/',
         ':', ';', '<', '=', '>', '?', '@', '[', '\\', ']', '^', '_', '`', '{', '|', '}', '~'
     ].into_iter().enumerate().map(|(order, ch)| (ch, order)).collect();
+
+    /// Latin letters with diacritics and ligatures mapped to their base letters
+    static ref BASE: HashMap<char, (char, Option<char>)> = vec![
+        ("A", "ÀÁÂÃÄÅĀĂĄǍ"), ("a", "àáâãäåāăąǎ"), ("AE", "Æ"), ("ae", "æ"),
+        ("C", "ÇĆĈĊČ"), ("c", "çćĉċč"), ("D", "ĎĐÐ"), ("d", "ďđð"),
+        ("E", "ÈÉÊËĒĔĖĘĚ"), ("e", "èéêëēĕėęě"), ("G", "ĜĞĠĢ"), ("g", "ĝğġģ"),
+        ("H", "ĤĦ"), ("h", "ĥħ"), ("I", "ÌÍÎÏĨĪĬĮİ"), ("i", "ìíîïĩīĭįı"),
+        ("J", "Ĵ"), ("j", "ĵ"), ("K", "Ķ"), ("k", "ķ"), ("L", "ĹĻĽĿŁ"), ("l", "ĺļľŀł"),
+        ("N", "ÑŃŅŇ"), ("n", "ñńņň"), ("O", "ÒÓÔÕÖØŌŎŐ"), ("o", "òóôõöøōŏő"),
+        ("OE", "Œ"), ("oe", "œ"), ("R", "ŔŖŘ"), ("r", "ŕŗř"),
+        ("S", "ŚŜŞŠ"), ("s", "śŝşš"), ("ss", "ß"), ("T", "ŢŤŦ"), ("t", "ţťŧ"),
+        ("TH", "Þ"), ("th", "þ"), ("U", "ÙÚÛÜŨŪŬŮŰŲ"), ("u", "ùúûüũūŭůűų"),
+        ("W", "Ŵ"), ("w", "ŵ"), ("Y", "ÝŶŸ"), ("y", "ýÿŷ"), ("Z", "ŹŻŽ"), ("z", "źżž"),
+    ].into_iter().flat_map(|(base, letters)| {
+        let mut base = base.chars();
+        let base = (base.next().unwrap_or_default(), base.next());
+        letters.chars().map(move |ch| (ch, base))
+    }).collect();
+}
+
+/// Position of the character, unknown characters follow the known ones in code point order
+fn weight(ch: char) -> usize {
+    ORDER.get(&ch).copied().unwrap_or(ORDER.len() + ch as usize)
+}
+
+/// Characters of the text with diacritics removed and ligatures expanded
+fn base_chars(text: &str) -> impl Iterator<Item = char> + '_ {
+    text.chars().flat_map(|ch| {
+        let (first, second) = BASE.get(&ch).copied().unwrap_or((ch, None));
+        std::iter::once(first).chain(second)
+    })
 }
 
+/// Compares the texts by the base letters first, then letters with diacritics
+/// follow their base letters, e.g. `Eco < Émile < Ende`
 pub fn collation(a: &str, b: &str) -> Ordering {
-    let mut a_chars = a.chars();
-    let mut b_chars = b.chars();
-
-    loop {
-        match (a_chars.next(), b_chars.next()) {
-            (Some(a_char), Some(b_char)) => {
-                let a_pos = ORDER.get(&a_char).unwrap_or(&usize::MAX);
-                let b_pos = ORDER.get(&b_char).unwrap_or(&usize::MAX);
-                match a_pos.cmp(b_pos) {
-                    Ordering::Equal => continue,
-                    other => return other,
-                }
+    base_chars(a)
+        .map(weight)
+        .cmp(base_chars(b).map(weight))
+        .then_with(|| a.chars().map(weight).cmp(b.chars().map(weight)))
+}
+
+/// Case-insensitive variant of [collation], the case only breaks the ties
+pub fn collation_ci(a: &str, b: &str) -> Ordering {
+    folded_chars(a)
+        .map(weight)
+        .cmp(folded_chars(b).map(weight))
+        .then_with(|| collation(a, b))
+}
+
+/// Case-insensitive variant of [collation] which compares the digit runs as numbers,
+/// e.g. `Том 2 < Том 10`
+pub fn collation_natural(a: &str, b: &str) -> Ordering {
+    Tokens(folded_chars(a).peekable())
+        .cmp(Tokens(folded_chars(b).peekable()))
+        .then_with(|| collation(a, b))
+}
+
+/// Lowercase characters of the text with diacritics removed and ligatures expanded
+fn folded_chars(text: &str) -> impl Iterator<Item = char> + '_ {
+    base_chars(text).flat_map(char::to_lowercase)
+}
+
+/// Character or number, numbers take the place of the digits
+#[derive(Debug, PartialEq, Eq)]
+enum Token {
+    Char(usize),
+    /// Digits without the leading zeros
+    Number(String),
+}
+impl Ord for Token {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let digits = weight('0');
+        match (self, other) {
+            (Token::Char(a), Token::Char(b)) => a.cmp(b),
+            (Token::Char(a), Token::Number(_)) => a.cmp(&digits),
+            (Token::Number(_), Token::Char(b)) => digits.cmp(b),
+            (Token::Number(a), Token::Number(b)) => a.len().cmp(&b.len()).then_with(|| a.cmp(b)),
+        }
+    }
+}
+impl PartialOrd for Token {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Splits the characters into tokens, every run of the ASCII digits is one number
+struct Tokens<I: Iterator<Item = char>>(Peekable<I>);
+impl<I: Iterator<Item = char>> Iterator for Tokens<I> {
+    type Item = Token;
+
+    fn next(&mut self) -> Option<Token> {
+        let ch = self.0.next()?;
+        if !ch.is_ascii_digit() {
+            return Some(Token::Char(weight(ch)));
+        }
+        let mut digits = String::new();
+        if ch != '0' {
+            digits.push(ch);
+        }
+        while let Some(digit) = self.0.next_if(char::is_ascii_digit) {
+            if !(digits.is_empty() && digit == '0') {
+                digits.push(digit);
             }
-            (Some(_), None) => return Ordering::Greater,
-            (None, Some(_)) => return Ordering::Less,
-            (None, None) => return Ordering::Equal,
         }
+        Some(Token::Number(digits))
     }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sorted(mut names: Vec<&str>) -> Vec<&str> {
+        names.sort_by(|a, b| collation(a, b));
+        names
+    }
+
+    #[test]
+    fn cyrillic() {
+        assert_eq!(
+            sorted(vec![
+                "Їжак",
+                "Яковлев",
+                "Іванов",
+                "Ґудзь",
+                "Євтушенко",
+                "Ёлкин",
+                "Иванов"
+            ]),
+            vec![
+                "Ґудзь",
+                "Ёлкин",
+                "Євтушенко",
+                "Иванов",
+                "Іванов",
+                "Їжак",
+                "Яковлев"
+            ]
+        );
+        assert_eq!(
+            sorted(vec!["Шупа", "Ўладзь", "Усов", "Фёдаров", "Ўсцін"]),
+            vec!["Усов", "Ўладзь", "Ўсцін", "Фёдаров", "Шупа"]
+        );
+    }
+
+    #[test]
+    fn diacritics() {
+        assert_eq!(
+            sorted(vec![
+                "Zola", "Ende", "Émile", "Eco", "Čapek", "Capote", "Dumas"
+            ]),
+            vec!["Čapek", "Capote", "Dumas", "Eco", "Émile", "Ende", "Zola"]
+        );
+        assert_eq!(
+            sorted(vec!["Strauss", "Straße", "Strass", "Łem", "Lem"]),
+            vec!["Lem", "Łem", "Strass", "Straße", "Strauss"]
+        );
+        assert_eq!(collation("Émile", "Emile"), Ordering::Greater);
+        assert_eq!(collation("Émile", "Émile"), Ordering::Equal);
+    }
+
+    #[test]
+    fn mixed_scripts() {
+        assert_eq!(
+            sorted(vec![
+                "Ørsted",
+                "Шевченко",
+                "1984",
+                "Österberg",
+                "Казанський",
+                "Їжакевич",
+                "Álvarez",
+                "Кэрролл",
+                "Abbott",
+                "Ірваненко",
+            ]),
+            vec![
+                "Ірваненко",
+                "Їжакевич",
+                "Казанський",
+                "Кэрролл",
+                "Шевченко",
+                "Abbott",
+                "Álvarez",
+                "Ørsted",
+                "Österberg",
+                "1984",
+            ]
+        );
+    }
+
+    #[test]
+    fn case_insensitive() {
+        let mut names = vec![
+            "Яковлев",
+            "ауэрбах",
+            "Ёлкин",
+            "елкин",
+            "Аверин",
+            "Émile",
+            "eco",
+        ];
+        names.sort_by(|a, b| collation_ci(a, b));
+        assert_eq!(
+            names,
+            vec![
+                "Аверин",
+                "ауэрбах",
+                "елкин",
+                "Ёлкин",
+                "Яковлев",
+                "eco",
+                "Émile"
+            ]
+        );
+        assert_eq!(collation_ci("Том", "том"), Ordering::Less);
+        assert_eq!(collation_ci("том", "том"), Ordering::Equal);
+    }
 
+    #[test]
+    fn natural() {
+        let mut names = vec![
+            "Том 10",
+            "том 2",
+            "Том 1",
+            "Том 02",
+            "Том",
+            "Том 2. Часть 1",
+        ];
+        names.sort_by(|a, b| collation_natural(a, b));
+        assert_eq!(
+            names,
+            vec![
+                "Том",
+                "Том 1",
+                "Том 02",
+                "том 2",
+                "Том 2. Часть 1",
+                "Том 10"
+            ]
+        );
+        assert_eq!(collation_natural("x9", "x10"), Ordering::Less);
+        assert_eq!(collation_natural("x00", "x0"), Ordering::Greater);
+        assert_eq!(collation_natural("Том 1", "Том A"), Ordering::Greater);
+    }
+
+    #[test]
+    fn unknown() {
+        assert_eq!(collation("Ω", "Ψ"), Ordering::Greater);
+        assert_eq!(collation("~", "Ω"), Ordering::Less);
+        assert_eq!(collation("漢", "漢"), Ordering::Equal);
+    }
+}
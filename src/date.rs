@@ -0,0 +1,150 @@
+use rusqlite::types::{ToSql, ToSqlOutput};
+
+use std::{fmt, str::FromStr};
+
+use crate::{Error, Result};
+
+/// Calendar date in the `YYYY-MM-DD` format of the `dates` table
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Date {
+    pub year: u16,
+    pub month: u8,
+    pub day: u8,
+}
+impl Date {
+    pub fn new(year: u16, month: u8, day: u8) -> Result<Self> {
+        if month == 0 || month > 12 || day == 0 || day > days_in_month(year, month) {
+            return Err(Error::InvalidArgument(format!(
+                "date {year:04}-{month:02}-{day:02}"
+            )));
+        }
+        Ok(Self { year, month, day })
+    }
+
+    /// Returns the date `days` days before
+    pub fn days_before(&self, days: u32) -> Self {
+        Self::from_days(self.to_days() - i64::from(days))
+    }
+
+    /// Returns number of days since 1970-01-01
+    fn to_days(self) -> i64 {
+        let (month, day) = (i64::from(self.month), i64::from(self.day));
+        let year = i64::from(self.year) - i64::from(month <= 2);
+        let era = year.div_euclid(400);
+        let yoe = year - era * 400;
+        let doy = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+        let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+        era * 146097 + doe - 719468
+    }
+
+    /// Returns the date by number of days since 1970-01-01
+    fn from_days(days: i64) -> Self {
+        let days = days + 719468;
+        let era = days.div_euclid(146097);
+        let doe = days - era * 146097;
+        let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+        let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+        let mp = (5 * doy + 2) / 153;
+        let day = doy - (153 * mp + 2) / 5 + 1;
+        let month = if mp < 10 { mp + 3 } else { mp - 9 };
+        let year = yoe + era * 400 + i64::from(month <= 2);
+        Self {
+            year: year.clamp(0, i64::from(u16::MAX)) as u16,
+            month: month as u8,
+            day: day as u8,
+        }
+    }
+}
+impl FromStr for Date {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let invalid = || Error::InvalidArgument(format!("date '{s}', expected YYYY-MM-DD"));
+        let mut parts = s.splitn(3, '-');
+        match (parts.next(), parts.next(), parts.next()) {
+            (Some(year), Some(month), Some(day)) if year.len() == 4 => {
+                let year = year.parse().map_err(|_| invalid())?;
+                let month = month.parse().map_err(|_| invalid())?;
+                let day = day.parse().map_err(|_| invalid())?;
+                Self::new(year, month, day)
+            }
+            _ => Err(invalid()),
+        }
+    }
+}
+impl fmt::Display for Date {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            formatter,
+            "{:04}-{:02}-{:02}",
+            self.year, self.month, self.day
+        )
+    }
+}
+impl ToSql for Date {
+    fn to_sql(&self) -> rusqlite::Result<ToSqlOutput<'_>> {
+        Ok(ToSqlOutput::from(self.to_string()))
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for Date {
+    fn serialize<S: serde::Serializer>(
+        &self,
+        serializer: S,
+    ) -> std::result::Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Date {
+    fn deserialize<D: serde::Deserializer<'de>>(
+        deserializer: D,
+    ) -> std::result::Result<Self, D::Error> {
+        let date = String::deserialize(deserializer)?;
+        date.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+fn days_in_month(year: u16, month: u8) -> u8 {
+    match month {
+        2 if year.is_multiple_of(4) && (!year.is_multiple_of(100) || year.is_multiple_of(400)) => {
+            29
+        }
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse() -> Result<()> {
+        let date = "2024-06-01".parse::<Date>()?;
+        assert_eq!(date, Date::new(2024, 6, 1)?);
+        assert_eq!(date.to_string(), "2024-06-01");
+
+        assert!("2024-02-30".parse::<Date>().is_err());
+        assert!("2024-13-01".parse::<Date>().is_err());
+        assert!("2024-06".parse::<Date>().is_err());
+        assert!("24-06-01".parse::<Date>().is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn days_before() -> Result<()> {
+        let date = Date::new(2024, 3, 1)?;
+        assert_eq!(date.days_before(0), date);
+        assert_eq!(date.days_before(1), Date::new(2024, 2, 29)?);
+        assert_eq!(date.days_before(366), Date::new(2023, 3, 1)?);
+        assert_eq!(Date::new(1970, 1, 1)?.to_days(), 0);
+        assert_eq!(
+            Date::new(2024, 6, 30)?.days_before(29),
+            Date::new(2024, 6, 1)?
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,118 @@
+use std::{fmt, io};
+
+/// Result of the OpdsApi operations
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// Errors of the OpdsApi operations
+#[derive(Debug)]
+pub enum Error {
+    /// SQLite failed to open the database or to run the query
+    Sqlite(rusqlite::Error),
+    /// Failed to read the INP file
+    Io(io::Error),
+    /// Requested item does not exist
+    NotFound(String),
+    /// Argument or input data is malformed
+    InvalidArgument(String),
+    /// Library archive, FB2 document or cover image is malformed
+    Format(String),
+    /// Queries do not match the database schema of the version
+    Schema { version: u32, failures: Vec<String> },
+    /// Schema version is newer than the supported one
+    UnsupportedVersion(u32),
+    /// Too many calls are waiting for the connection
+    Busy,
+    /// Call was cancelled before it completed
+    Cancelled,
+}
+impl fmt::Display for Error {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Sqlite(e) => write!(fmt, "SQLite error: {e}"),
+            Self::Io(e) => write!(fmt, "I/O error: {e}"),
+            Self::NotFound(what) => write!(fmt, "{what} not found"),
+            Self::InvalidArgument(what) => write!(fmt, "Invalid argument: {what}"),
+            Self::Format(what) => write!(fmt, "Malformed file: {what}"),
+            Self::Schema { version, failures } => write!(
+                fmt,
+                "{} queries do not match schema version {version}: {}",
+                failures.len(),
+                failures.join("; ")
+            ),
+            Self::UnsupportedVersion(version) => write!(
+                fmt,
+                "Unsupported schema version {version}, expected at most {}",
+                crate::schema::VERSION
+            ),
+            Self::Busy => write!(fmt, "All connections are busy"),
+            Self::Cancelled => write!(fmt, "Call was cancelled"),
+        }
+    }
+}
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Sqlite(e) => Some(e),
+            Self::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+impl From<rusqlite::Error> for Error {
+    fn from(e: rusqlite::Error) -> Self {
+        Self::Sqlite(e)
+    }
+}
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+impl From<zip::result::ZipError> for Error {
+    fn from(e: zip::result::ZipError) -> Self {
+        match e {
+            zip::result::ZipError::Io(e) => Self::Io(e),
+            e => Self::Format(e.to_string()),
+        }
+    }
+}
+impl From<quick_xml::Error> for Error {
+    fn from(e: quick_xml::Error) -> Self {
+        Self::Format(e.to_string())
+    }
+}
+impl From<quick_xml::events::attributes::AttrError> for Error {
+    fn from(e: quick_xml::events::attributes::AttrError) -> Self {
+        Self::Format(e.to_string())
+    }
+}
+impl From<base64::DecodeError> for Error {
+    fn from(e: base64::DecodeError) -> Self {
+        Self::Format(e.to_string())
+    }
+}
+#[cfg(feature = "thumbnail")]
+impl From<image::ImageError> for Error {
+    fn from(e: image::ImageError) -> Self {
+        match e {
+            image::ImageError::IoError(e) => Self::Io(e),
+            e => Self::Format(e.to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fmt() {
+        assert_eq!(
+            "Author 1/2/3 not found",
+            Error::NotFound(String::from("Author 1/2/3")).to_string()
+        );
+        assert_eq!("All connections are busy", Error::Busy.to_string());
+        let e = Error::from(rusqlite::Error::QueryReturnedNoRows);
+        assert!(std::error::Error::source(&e).is_some());
+    }
+}
@@ -0,0 +1,94 @@
+use serde::{de::DeserializeOwned, Serialize};
+
+use std::io::{BufRead, Write};
+
+use crate::{Error, Result};
+
+/// Writes the items as JSON lines, one JSON object per line, returns number of the written items
+pub fn write_json_lines<W, T>(mut out: W, items: &[T]) -> Result<usize>
+where
+    W: Write,
+    T: Serialize,
+{
+    for (idx, item) in items.iter().enumerate() {
+        serde_json::to_writer(&mut out, item).map_err(|e| {
+            if e.is_io() {
+                Error::Io(e.into())
+            } else {
+                Error::InvalidArgument(format!("JSON item #{idx}: {e}"))
+            }
+        })?;
+        out.write_all(b"\n")?;
+    }
+    out.flush()?;
+    Ok(items.len())
+}
+
+/// Reads the items written by [write_json_lines], empty lines are skipped
+pub fn read_json_lines<R, T>(input: R) -> Result<Vec<T>>
+where
+    R: BufRead,
+    T: DeserializeOwned,
+{
+    let mut items = Vec::new();
+    for (idx, line) in input.lines().enumerate() {
+        let line = line?;
+        if !line.trim().is_empty() {
+            let item = serde_json::from_str(&line)
+                .map_err(|e| Error::InvalidArgument(format!("JSON line {}: {e}", idx + 1)))?;
+            items.push(item);
+        }
+    }
+    Ok(items)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Author, Book, Date, Found, Value};
+
+    #[test]
+    fn json_lines() -> Result<()> {
+        let author = Author::new(
+            Value::new(1, "Анна"),
+            Value::new(2, ""),
+            Value::new(3, "Велес"),
+        );
+        let book = Book::new(
+            7,
+            "Name",
+            None,
+            None,
+            vec![author.clone()],
+            100,
+            "2024-06-01",
+        )
+        .with_lang("ru");
+        let found = vec![Found::Author(author), Found::Book(book)];
+
+        let mut out = Vec::new();
+        assert_eq!(write_json_lines(&mut out, &found)?, 2);
+        let text = String::from_utf8(out).expect("utf-8");
+        assert_eq!(text.lines().count(), 2);
+        assert!(text.starts_with(r#"{"author":{"first_name":{"id":1,"value":"Анна"}"#));
+        assert!(text.contains(r#""added":"2024-06-01""#));
+
+        let read = read_json_lines::<_, Found>(format!("{text}\n").as_bytes())?;
+        assert_eq!(read, found);
+        Ok(())
+    }
+
+    #[test]
+    fn date() -> Result<()> {
+        let date = Date::new(2024, 6, 1)?;
+        let json = serde_json::to_string(&date).map_err(std::io::Error::from)?;
+        assert_eq!(json, r#""2024-06-01""#);
+        assert_eq!(read_json_lines::<_, Date>(json.as_bytes())?, vec![date]);
+        let result = read_json_lines::<_, Date>(&b"\"2024-06-01\"\n\n\"2024-13-01\""[..]);
+        assert!(
+            matches!(&result, Err(Error::InvalidArgument(what)) if what.starts_with("JSON line 3:")),
+            "{result:?}"
+        );
+        Ok(())
+    }
+}
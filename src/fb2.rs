@@ -0,0 +1,224 @@
+use base64::{engine::general_purpose::STANDARD, Engine};
+use log::debug;
+use quick_xml::{
+    events::{BytesStart, Event},
+    Reader,
+};
+
+use std::{
+    fs::File,
+    io::{BufRead, BufReader},
+    path::Path,
+};
+
+use crate::Result;
+
+/// Cover image of the book
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cover {
+    pub mime: String,
+    pub bytes: Vec<u8>,
+}
+
+/// Parts of the FB2 `<description>` shown in the catalog
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Description {
+    /// Text of `<title-info><annotation>`, one line per paragraph
+    pub annotation: Option<String>,
+    /// Binary referenced by `<title-info><coverpage>`
+    pub cover: Option<Cover>,
+}
+impl Description {
+    /// Parses the FB2 file
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        debug!("open <- {}", path.as_ref().display());
+        Self::read(BufReader::new(File::open(path)?))
+    }
+
+    /// Parses the FB2 document
+    ///
+    /// Reading stops as soon as the annotation and the cover are found
+    pub fn read<R: BufRead>(input: R) -> Result<Self> {
+        let mut reader = Reader::from_reader(input);
+        let mut buf = Vec::new();
+        let mut path: Vec<Vec<u8>> = Vec::new();
+        let mut annotation: Vec<String> = Vec::new();
+        let mut href: Option<String> = None;
+        let mut binary: Option<(String, String)> = None;
+        let mut description = Self::default();
+
+        loop {
+            match reader.read_event_into(&mut buf)? {
+                Event::Start(e) => {
+                    let name = e.local_name().as_ref().to_vec();
+                    if path.len() == 1 && name == b"binary" {
+                        let id = attribute(&reader, &e, b"id")?;
+                        if id.is_some() && id == href {
+                            let mime = attribute(&reader, &e, b"content-type")?;
+                            binary = Some((mime.unwrap_or_default(), String::new()));
+                        }
+                    }
+                    if in_annotation(&path) && name == b"p" {
+                        annotation.push(String::new());
+                    }
+                    if in_coverpage(&path) && name == b"image" {
+                        href = cover_href(&reader, &e)?;
+                    }
+                    path.push(name);
+                }
+                Event::Empty(e) if in_coverpage(&path) && e.local_name().as_ref() == b"image" => {
+                    href = cover_href(&reader, &e)?;
+                }
+                Event::Text(e) => {
+                    if let Some((_, data)) = binary.as_mut() {
+                        data.push_str(&e.unescape()?);
+                    } else if in_annotation(&path) {
+                        let text = e.unescape()?;
+                        match annotation.last_mut() {
+                            Some(line) => line.push_str(&text),
+                            None => annotation.push(text.into_owned()),
+                        }
+                    }
+                }
+                Event::End(_) => {
+                    let name = path.pop().unwrap_or_default();
+                    if name == b"description" {
+                        let lines = annotation
+                            .iter()
+                            .map(|line| line.split_whitespace().collect::<Vec<_>>().join(" "))
+                            .filter(|line| !line.is_empty())
+                            .collect::<Vec<_>>();
+                        if !lines.is_empty() {
+                            description.annotation = Some(lines.join("\n"));
+                        }
+                        if href.is_none() {
+                            break;
+                        }
+                    }
+                    if let Some((mime, data)) = binary.take() {
+                        let data = data.split_whitespace().collect::<String>();
+                        let bytes = STANDARD.decode(data)?;
+                        let mime = if mime.is_empty() {
+                            String::from(guess_mime(&bytes))
+                        } else {
+                            mime
+                        };
+                        description.cover = Some(Cover { mime, bytes });
+                        break;
+                    }
+                }
+                Event::Eof => break,
+                _ => {}
+            }
+            buf.clear();
+        }
+        Ok(description)
+    }
+}
+
+fn in_title_info(path: &[Vec<u8>]) -> bool {
+    path.len() >= 3 && path[1] == b"description" && path[2] == b"title-info"
+}
+
+fn in_annotation(path: &[Vec<u8>]) -> bool {
+    in_title_info(path) && path.get(3).is_some_and(|name| name == b"annotation")
+}
+
+fn in_coverpage(path: &[Vec<u8>]) -> bool {
+    in_title_info(path) && path.len() == 4 && path[3] == b"coverpage"
+}
+
+/// Returns decoded value of the attribute by its local name
+fn attribute<R>(reader: &Reader<R>, e: &BytesStart, name: &[u8]) -> Result<Option<String>> {
+    for attr in e.attributes() {
+        let attr = attr?;
+        if attr.key.local_name().as_ref() == name {
+            let value = attr.decode_and_unescape_value(reader.decoder())?;
+            return Ok(Some(value.into_owned()));
+        }
+    }
+    Ok(None)
+}
+
+fn cover_href<R>(reader: &Reader<R>, e: &BytesStart) -> Result<Option<String>> {
+    let href = attribute(reader, e, b"href")?;
+    Ok(href.map(|href| href.trim_start_matches('#').to_owned()))
+}
+
+/// Guesses mime type of the image by its signature
+fn guess_mime(bytes: &[u8]) -> &'static str {
+    if bytes.starts_with(b"\x89PNG") {
+        "image/png"
+    } else if bytes.starts_with(b"GIF8") {
+        "image/gif"
+    } else {
+        "image/jpeg"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FB2: &str = r##"<?xml version="1.0" encoding="utf-8"?>
+<FictionBook xmlns="http://www.gribuser.ru/xml/fictionbook/2.0" xmlns:l="http://www.w3.org/1999/xlink">
+ <description>
+  <title-info>
+   <genre>sf</genre>
+   <book-title>Title</book-title>
+   <annotation>
+    <p>First   paragraph</p>
+    <p>Second <emphasis>one</emphasis> &amp; more</p>
+   </annotation>
+   <coverpage><image l:href="#cover.png"/></coverpage>
+  </title-info>
+ </description>
+ <body><section><p>Text</p></section></body>
+ <binary id="other.jpg" content-type="image/jpeg">AAAA</binary>
+ <binary id="cover.png" content-type="image/png">
+  iVBORw0K
+  GgoA
+ </binary>
+</FictionBook>"##;
+
+    #[test]
+    fn read() -> anyhow::Result<()> {
+        let description = Description::read(FB2.as_bytes())?;
+        assert_eq!(
+            description.annotation.as_deref(),
+            Some("First paragraph\nSecond one & more")
+        );
+        assert_eq!(
+            description.cover,
+            Some(Cover {
+                mime: String::from("image/png"),
+                bytes: b"\x89PNG\r\n\x1a\n\x00".to_vec(),
+            })
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn read_without_cover() -> anyhow::Result<()> {
+        let fb2 = r#"<FictionBook><description><title-info>
+            <annotation>Plain text</annotation>
+            </title-info></description><body/></FictionBook>"#;
+        let description = Description::read(fb2.as_bytes())?;
+        assert_eq!(description.annotation.as_deref(), Some("Plain text"));
+        assert_eq!(description.cover, None);
+        Ok(())
+    }
+
+    #[test]
+    fn read_cp1251() -> anyhow::Result<()> {
+        let mut fb2 = br#"<?xml version="1.0" encoding="windows-1251"?>
+            <FictionBook><description><title-info><annotation><p>"#
+            .to_vec();
+        fb2.extend_from_slice(&[0xCF, 0xF0, 0xE8, 0xE2, 0xE5, 0xF2]);
+        fb2.extend_from_slice(b"</p></annotation></title-info></description></FictionBook>");
+
+        let description = Description::read(fb2.as_slice())?;
+        assert_eq!(description.annotation.as_deref(), Some("Привет"));
+        Ok(())
+    }
+}
@@ -0,0 +1,22 @@
+use std::fmt;
+
+use crate::{Author, Book, Serie};
+
+/// Item of the full-text search result
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum Found {
+    Author(Author),
+    Serie(Serie),
+    Book(Book),
+}
+impl fmt::Display for Found {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Author(author) => write!(fmt, "Author: {author}"),
+            Self::Serie(serie) => write!(fmt, "Serie: {serie}"),
+            Self::Book(book) => write!(fmt, "Book: {book}"),
+        }
+    }
+}
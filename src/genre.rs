@@ -0,0 +1,40 @@
+use std::fmt;
+
+/// Genre of the book resolved to its human-readable names
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Genre {
+    pub id: u32,
+    /// Code of the genre in the INP file, e.g. "sf_fantasy"
+    pub code: String,
+    pub name: String,
+    pub meta: String,
+}
+impl Genre {
+    pub fn new<T: Into<String>>(id: u32, code: T, name: T, meta: T) -> Self {
+        Self {
+            id,
+            code: code.into(),
+            name: name.into(),
+            meta: meta.into(),
+        }
+    }
+}
+impl fmt::Display for Genre {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(formatter, "{} / {}", self.meta, self.name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fmt() {
+        assert_eq!(
+            "Фантастика / Фэнтези",
+            format!("{}", &Genre::new(1, "sf_fantasy", "Фэнтези", "Фантастика"))
+        );
+    }
+}
@@ -0,0 +1,658 @@
+/// Genre definitions: (code, genre, meta)
+pub(crate) const GENRES: [(&str, &str, &str); 315] = [
+    ("sci_biochem", "Биохимия", "Наука, Образование"),
+    ("sci_anachem", "Аналитическая химия", "Наука, Образование"),
+    ("sci_abstract", "Рефераты", "Наука, Образование"),
+    ("sci_biophys", "Биофизика", "Наука, Образование"),
+    ("sci_orgchem", "Органическая химия", "Наука, Образование"),
+    ("paper_work", "Делопроизводство", "Наука, Образование"),
+    ("small_business", "Малый бизнес", "Наука, Образование"),
+    ("industries", "Отраслевые издания", "Наука, Образование"),
+    ("comp_programming", "Программирование", "Наука, Образование"),
+    (
+        "foreign_language",
+        "Иностранные языки",
+        "Наука, Образование",
+    ),
+    (
+        "psy_theraphy",
+        "Психотерапия и консультирование",
+        "Наука, Образование",
+    ),
+    ("comp_soft", "Программы", "Наука, Образование"),
+    ("comp_osnet", "ОС и Сети", "Наука, Образование"),
+    ("psy_childs", "Детская психология", "Наука, Образование"),
+    ("sci_crib", "Шпаргалки", "Наука, Образование"),
+    ("accounting", "Бухучет и аудит", "Наука, Образование"),
+    (
+        "management",
+        "Управление, подбор персонала",
+        "Наука, Образование",
+    ),
+    ("marketing", "Маркетинг, PR, реклама", "Наука, Образование"),
+    ("job_hunting", "Поиск работы, карьера", "Наука, Образование"),
+    ("personal_finance", "Личные финансы", "Наука, Образование"),
+    (
+        "visual_arts",
+        "Изобразительное искусство, фотография",
+        "Наука, Образование",
+    ),
+    (
+        "psy_sex_and_family",
+        "Секс и семейная психология",
+        "Наука, Образование",
+    ),
+    ("stock", "Ценные бумаги, инвестиции", "Наука, Образование"),
+    ("sci_business", "Деловая литература", "Наука, Образование"),
+    ("sci_physchem", "Физическая химия", "Наука, Образование"),
+    ("sf_history", "Альтернативная история", "Фантастика"),
+    ("nsf", "Ненаучная фантастика", "Фантастика"),
+    ("sf_fantasy_irony", "Ироническое фэнтези", "Фантастика"),
+    ("historical_fantasy", "Историческое фэнтези", "Фантастика"),
+    ("fantasy_alt_hist", "Альтернативная история", "Фантастика"),
+    ("sf_irony", "Ироническая фантастика", "Фантастика"),
+    ("sf_space_opera", "Космоопера", "Фантастика"),
+    ("sf_action", "Боевая фантастика", "Фантастика"),
+    ("sf_epic", "Эпическая фантастика", "Фантастика"),
+    ("sf_heroic", "Героическая фантастика", "Фантастика"),
+    ("sf_detective", "Детективная фантастика", "Фантастика"),
+    ("sf_cyberpunk", "Киберпанк", "Фантастика"),
+    ("sf_space", "Космическая фантастика", "Фантастика"),
+    (
+        "sf_social",
+        "Социально-психологическая фантастика",
+        "Фантастика",
+    ),
+    ("sf_horror", "Ужасы", "Фантастика"),
+    ("sf_humor", "Юмористическая фантастика", "Фантастика"),
+    ("humor_fantasy", "Юмористическая фэнтази", "Фантастика"),
+    ("sf_fantasy", "Фэнтези", "Фантастика"),
+    ("sf", "Научная Фантастика", "Фантастика"),
+    ("sf_all", "Научная Фантастика", "Фантастика"),
+    (
+        "det_classic",
+        "Классический детектив",
+        "Детективы и Триллеры",
+    ),
+    ("thriller_techno", "Техно триллер", "Детективы и Триллеры"),
+    (
+        "det_cozy",
+        "Дамский детективный роман",
+        "Детективы и Триллеры",
+    ),
+    ("det_police", "Полицейский детектив", "Детективы и Триллеры"),
+    ("det_action", "Боевик", "Детективы и Триллеры"),
+    (
+        "det_irony",
+        "Иронический детектив, дамский детективный роман",
+        "Детективы и Триллеры",
+    ),
+    (
+        "det_history",
+        "Исторический детектив",
+        "Детективы и Триллеры",
+    ),
+    (
+        "det_espionage",
+        "Шпионский детектив",
+        "Детективы и Триллеры",
+    ),
+    ("det_crime", "Криминальный детектив", "Детективы и Триллеры"),
+    (
+        "det_political",
+        "Политический детектив",
+        "Детективы и Триллеры",
+    ),
+    ("det_maniac", "Про маньяков", "Детективы и Триллеры"),
+    ("det_hard", "Крутой детектив", "Детективы и Триллеры"),
+    (
+        "thriller_medical",
+        "Медицинский триллер",
+        "Детективы и Триллеры",
+    ),
+    (
+        "thriller_legal",
+        "Юридический триллер",
+        "Детективы и Триллеры",
+    ),
+    (
+        "thriller_psychology",
+        "Психологический триллер",
+        "Детективы и Триллеры",
+    ),
+    ("thriller", "Триллер", "Детективы и Триллеры"),
+    ("detective", "Детективы", "Детективы и Триллеры"),
+    ("det_all", "Детективы", "Детективы и Триллеры"),
+    ("prose", "Проза", "Проза"),
+    ("prose_all", "Проза", "Проза"),
+    ("dissident", "Антисоветская литература", "Проза"),
+    ("prose_classic", "Классическая проза", "Проза"),
+    ("prose_history", "Историческая проза", "Проза"),
+    ("ya", "Подростковая литература", "Проза"),
+    ("prose_sentimental", "Сентиментальная проза", "Проза"),
+    ("sagas", "Семейный роман/Семейная сага", "Проза"),
+    ("short_story", "Рассказ", "Проза"),
+    (
+        "prose_contemporary",
+        "Современная русская и зарубежная проза",
+        "Проза",
+    ),
+    ("prose_counter", "Контркультура", "Проза"),
+    ("prose_rus_classic", "Русская классическая проза", "Проза"),
+    ("prose_su_classics", "Советская классическая проза", "Проза"),
+    (
+        "love_contemporary",
+        "Современные любовные романы",
+        "Любовные романы",
+    ),
+    (
+        "love_history",
+        "Исторические любовные романы",
+        "Любовные романы",
+    ),
+    (
+        "love_detective",
+        "Остросюжетные любовные романы",
+        "Любовные романы",
+    ),
+    ("love_short", "Короткие любовные романы", "Любовные романы"),
+    ("love_erotica", "Эротическая литература", "Любовные романы"),
+    ("love", "Любовные романы", "Любовные романы"),
+    ("love_all", "Любовные романы", "Любовные романы"),
+    ("adv_history", "Исторические приключения", "Приключения"),
+    ("adv_indian", "Вестерн, про индейцев ", "Приключения"),
+    ("adv_maritime", "Морские приключения", "Приключения"),
+    ("adv_geo", "Путешествия и география", "Приключения"),
+    ("adv_animal", "Природа и животные", "Приключения"),
+    ("adventure", "Приключения", "Приключения"),
+    ("adv_western", "Вестерн", "Приключения"),
+    ("adv_all", "Приключения", "Приключения"),
+    ("child_tale", "Сказки народов мира", "Литература для детей"),
+    ("child_verse", "Стихи для детей", "Литература для детей"),
+    ("child_prose", "Проза для детей", "Литература для детей"),
+    (
+        "religion_budda",
+        "Буддизм",
+        "Религия, духовность, Эзотерика",
+    ),
+    ("religion_rel", "Религия, духовность", "Эзотерика"),
+    ("religion_all", "Религия, духовность", "Эзотерика"),
+    ("child_sf", "Фантастика для детей", "Литература для детей"),
+    (
+        "child_det",
+        "Детская остросюжетная литература",
+        "Литература для детей",
+    ),
+    (
+        "child_adv",
+        "Приключения для детей и подростков",
+        "Приключения",
+    ),
+    (
+        "child_education",
+        "Детская образовательная литература",
+        "Литература для детей",
+    ),
+    ("children", "Детская литература", "Литература для детей"),
+    ("child_all", "Детская литература", "Литература для детей"),
+    ("poetry", "Поэзия", "Поэзия"),
+    ("poetry_all", "Поэзия", "Поэзия"),
+    ("experimental_poetry", "Экспериментальная поэзия", "Поэзия"),
+    ("visual_poetry", "Визуальная поэзия", "Поэзия"),
+    ("vers_libre", "Верлибры", "Поэзия"),
+    ("fable", "Басни", "Поэзия"),
+    ("in_verse", "Стихт", "Поэзия"),
+    ("epic_poetry", "Эпическая поэзия", "Поэзия"),
+    ("dramaturgy", "Драматургия", "Драматургия"),
+    ("dramaturgy_all", "Драматургия", "Драматургия"),
+    ("antique_ant", "Античная литература", "Старинное"),
+    (
+        "antique_european",
+        "Европейская старинная литература",
+        "Старинное",
+    ),
+    ("antique_russian", "Древнерусская литература", "Старинное"),
+    ("antique_east", "Древневосточная литература", "Старинное"),
+    ("antique_myths", "Мифы. Легенды. Эпос", "Фольклор"),
+    ("antique", "Старинная литература", "Старинное"),
+    ("antique_all", "Старинная литература", "Старинное"),
+    ("sci_history", "История", "Наука, Образование"),
+    (
+        "sci_psychology",
+        "Психология и психотерапия",
+        "Наука, Образование",
+    ),
+    (
+        "sci_culture",
+        "Культурология",
+        "Искусство, Искусствоведение, Дизайн",
+    ),
+    (
+        "culture_all",
+        "Культурология",
+        "Искусство, Искусствоведение, Дизайн",
+    ),
+    (
+        "sci_religion",
+        "Религиоведение",
+        "Религия, духовность, Эзотерика",
+    ),
+    ("sci_philosophy", "Философия", "Наука, Образование"),
+    ("sci_politics", "Политика", "Наука, Образование"),
+    ("sci_juris", "Юриспруденция", "Наука, Образование"),
+    (
+        "sci_linguistic",
+        "Языкознание, иностранные языки",
+        "Наука, Образование",
+    ),
+    ("sci_medicine", "Медицина", "Наука, Образование"),
+    ("sci_phys", "Физика", "Наука, Образование"),
+    ("sci_math", "Математика", "Наука, Образование"),
+    ("sci_chem", "Химия", "Наука, Образование"),
+    (
+        "sci_biology",
+        "Биология, биофизика, биохимия",
+        "Наука, Образование",
+    ),
+    ("trade", "Торговля", "Наука, Образование"),
+    ("global_economy", "Внешняя торговля", "Наука, Образование"),
+    ("real_estate", "Недвижимость", "Наука, Образование"),
+    ("sci_tech", "Технические науки", "Техника"),
+    ("tech_all", "Технические науки", "Техника"),
+    ("science", "Научная литература", "Наука, Образование"),
+    ("comp_www", "ОС и Сети, интернет", "Компьютеры и Интернет"),
+    (
+        "comp_hard",
+        "Компьютерное железо (аппаратное обеспечение), цифровая обработка сигналов",
+        "Компьютеры и Интернет",
+    ),
+    (
+        "comp_db",
+        "Программирование, программы, базы данных",
+        "Компьютеры и Интернет",
+    ),
+    (
+        "computers",
+        "Зарубежная компьютерная, околокомпьютерная литература ",
+        "Компьютеры и Интернет",
+    ),
+    (
+        "comp_all",
+        "Компьютерная литература",
+        "Компьютеры и Интернет",
+    ),
+    ("notes", "Партитуры", "Искусство, Искусствоведение, Дизайн"),
+    ("ref_encyc", "Энциклопедии", "Справочная литература"),
+    ("ref_dict", "Словари", "Справочная литература"),
+    ("sci_cosmos", "Астрономия и Космос", "Наука, Образование"),
+    ("ref_ref", "Справочники", "Справочная литература"),
+    ("ref_guide", "Руководства", "Справочная литература"),
+    ("sci_geo", "Геология и география", "Наука, Образование"),
+    (
+        "reference",
+        "Справочная литература",
+        "Справочная литература",
+    ),
+    ("ref_all", "Справочная литература", "Справочная литература"),
+    ("sf_fantasy_city", "Городское фэнтези", "Фантастика"),
+    (
+        "nonf_biography",
+        "Биографии и Мемуары",
+        "Документальная литература",
+    ),
+    (
+        "nonf_publicism",
+        "Публицистика",
+        "Документальная литература",
+    ),
+    (
+        "nonf_criticism",
+        "Критика",
+        "Искусство, Искусствоведение, Дизайн",
+    ),
+    (
+        "design",
+        "Искусство и Дизайн",
+        "Искусство, Искусствоведение, Дизайн",
+    ),
+    ("sci_state", "Государство и право", "Наука, Образование"),
+    (
+        "nonfiction",
+        "Документальная литература",
+        "Документальная литература",
+    ),
+    (
+        "nonf_all",
+        "Документальная литература",
+        "Документальная литература",
+    ),
+    ("sci_economy", "Экономика", "Наука, Образование"),
+    (
+        "religion_esoterics",
+        "Эзотерика, эзотерическая литература ",
+        "Религия, духовность, Эзотерика",
+    ),
+    (
+        "religion_self",
+        "Самосовершенствование",
+        "Религия, духовность, Эзотерика",
+    ),
+    ("sci_build", "Строительство и сопромат", "Техника"),
+    (
+        "religion",
+        "Религия, религиозная литература",
+        "Религия, духовность, Эзотерика",
+    ),
+    ("sci_radio", "Радиоэлектроника", "Техника"),
+    ("humor_anecdote", "Анекдоты", "Юмор"),
+    ("humor_prose", "Юмористическая проза", "Юмор"),
+    ("humor_verse", "Юмористические стихи, басни ", "Поэзия"),
+    ("sci_metal", "Металлургия", "Техника"),
+    ("humor", "Юмор", "Юмор"),
+    ("humor_all", "Юмор", "Юмор"),
+    ("home_cooking", "Кулинария", "Дом и семья"),
+    ("home_pets", "Домашние животные", "Дом и семья"),
+    ("sci_transport", "Транспорт и авиация", "Техника"),
+    ("home_crafts", "Хобби и ремесла", "Дом и семья"),
+    ("home_entertain", "Развлечения", "Дом и семья"),
+    ("home_health", "Здоровье", "Дом и семья"),
+    ("home_garden", "Сад и огород", "Дом и семья"),
+    ("home_diy", "Сделай сам", "Дом и семья"),
+    ("home_sport", "Боевые искусства, спорт", "Дом и семья"),
+    ("military_arts", "Боевые искусства", "Дом и семья"),
+    ("home_sex", "Семейные отношения, секс", "Дом и семья"),
+    ("home", "Домоводство", "Дом и семья"),
+    ("home_all", "Домоводство", "Дом и семья"),
+    ("other", "Неотсортированное", "Прочее"),
+    ("other_all", "Неотсортированное", "Прочее"),
+    ("prose_military", "Проза о войне", "Проза"),
+    ("sf_postapocalyptic", "Постапокалипсис", "Фантастика"),
+    ("banking", "Финансы", "Деловая литература"),
+    ("org_behavior", "Маркетинг, PR", "Деловая литература"),
+    ("popular_business", "Карьера, кадры", "Деловая литература"),
+    ("economics_ref", "Деловая литература", "Деловая литература"),
+    ("economics", "Экономика", "Деловая литература"),
+    (
+        "love_sf",
+        "Любовное фэнтези, любовно-фантастические романы ",
+        "Любовные романы",
+    ),
+    (
+        "sci_medicine_alternative",
+        "Альтернативная медицина",
+        "Наука, Образование",
+    ),
+    ("sci_philology", "Литературоведение", "Наука, Образование"),
+    (
+        "geo_guides",
+        "Путеводители, карты, атласы ",
+        "Справочная литература",
+    ),
+    ("periodic", "Журналы, газеты ", "Прочее"),
+    ("sf_litrpg", "ЛитРПГ", "Фантастика"),
+    ("det_su", "Советский детектив", "Детективы и Триллеры"),
+    (
+        "child_classical",
+        "Классическая детская литература",
+        "Литература для детей",
+    ),
+    ("child_tale_rus", "Русские сказки", "Литература для детей"),
+    (
+        "foreign_children",
+        "Зарубежная литература для детей",
+        "Литература для детей",
+    ),
+    (
+        "prose_game",
+        "Игры, упражнения для детей",
+        "Литература для детей",
+    ),
+    (
+        "nonf_military",
+        "Военная документалистика и аналитика",
+        "Документальная литература",
+    ),
+    (
+        "military_special",
+        "Военное дело",
+        "Документальная литература",
+    ),
+    ("military", "Военное дело", "Документальная литература"),
+    ("military_all", "Военное дело", "Документальная литература"),
+    (
+        "travel_notes",
+        "География, путевые заметки",
+        "Документальная литература",
+    ),
+    (
+        "sci_pedagogy",
+        "Педагогика, воспитание детей, литература для родителей ",
+        "Дом и семья",
+    ),
+    ("auto_regulations", "Автомобили и ПДД", "Дом и семья"),
+    ("home_collecting", "Коллекционирование", "Дом и семья"),
+    ("family", "Семейные отношения", "Дом и семья"),
+    (
+        "tbg_computers",
+        "Учебные пособия, самоучители",
+        "Компьютеры и Интернет",
+    ),
+    ("love_hard", "Порно", "Любовные романы"),
+    (
+        "sci_popular",
+        "Научно-популярная  литература",
+        "Наука, Образование",
+    ),
+    (
+        "sci_all",
+        "Научно-популярная  литература",
+        "Наука, Образование",
+    ),
+    ("military_history", "Военная история", "Наука, Образование"),
+    (
+        "sci_social_studies",
+        "Обществознание, социология",
+        "Наука, Образование",
+    ),
+    ("sci_zoo", "Зоология", "Наука, Образование"),
+    ("sci_botany", "Ботаника", "Наука, Образование"),
+    ("sci_ecology", "Экология", "Наука, Образование"),
+    ("sci_oriental", "Востоковедение", "Наука, Образование"),
+    (
+        "sci_theories",
+        "Альтернативные науки и научные теории",
+        "Наука, Образование",
+    ),
+    ("palmistry", "Хиромантия", "Наука, Образование"),
+    ("sci_veterinary", "Ветеринария", "Наука, Образование"),
+    ("comedy", "Комедия", "Драматургия"),
+    ("tragedy", "Трагедия", "Драматургия"),
+    ("drama", "Драма", "Драматургия"),
+    ("drama_antique", "Античная драма", "Драматургия"),
+    ("screenplays", "Сценарий", "Драматургия"),
+    ("scenarios", "Сценарии", "Драматургия"),
+    ("vaudeville", "Мистерия, буффонада, водевиль", "Драматургия"),
+    ("mystery", "Мистерия", "Драматургия"),
+    ("poetry_classical", "Классическая поэзия", "Поэзия"),
+    ("poetry_modern", "Современная поэзия", "Поэзия"),
+    (
+        "poetry_rus_classical",
+        "Классическая русская поэзия",
+        "Поэзия",
+    ),
+    ("poetry_rus_modern", "Современная русская поэзия", "Поэзия"),
+    (
+        "poetry_for_classical",
+        "Классическая зарубежная поэзия",
+        "Поэзия",
+    ),
+    (
+        "poetry_for_modern",
+        "Современная зарубежная поэзия",
+        "Поэзия",
+    ),
+    ("poetry_east", "Поэзия Востока", "Поэзия"),
+    ("lyrics", "Лирика", "Поэзия"),
+    ("song_poetry", "Песенная поэзия", "Поэзия"),
+    ("poem", "Поэма, эпическая поэзия", "Поэзия"),
+    (
+        "palindromes",
+        "Визуальная и экспериментальная поэзия, верлибры, палиндромы",
+        "Поэзия",
+    ),
+    (
+        "adv_modern",
+        "Приключения в современном мире",
+        "Приключения",
+    ),
+    ("tale_chivalry", "Рыцарский роман", "Приключения"),
+    ("adv_story", "Авантюрный роман", "Приключения"),
+    ("foreign_prose", "Зарубежная классическая проза", "Проза"),
+    (
+        "foreign_antique",
+        "Средневековая классическая проза",
+        "Проза",
+    ),
+    (
+        "literature_18",
+        "Классическая проза XVII-XVIII веков",
+        "Проза",
+    ),
+    ("literature_19", "Классическая проза ХIX века", "Проза"),
+    ("literature_20", "Классическая проза ХX века", "Проза"),
+    ("gothic_novel", "Готический роман", "Проза"),
+    ("prose_magic", "Магический реализм", "Проза"),
+    ("roman", "Роман", "Проза"),
+    ("epistolary_fiction", "Эпистолярная проза", "Проза"),
+    (
+        "prose_neformatny",
+        "Экспериментальная, неформатная проза",
+        "Проза",
+    ),
+    ("aphorisms", "Афоризмы, цитаты", "Проза"),
+    ("great_story", "Роман, повесть", "Проза"),
+    (
+        "story",
+        "Малые литературные формы прозы: рассказы, эссе, новеллы, феерия",
+        "Проза",
+    ),
+    ("essay", "Рассказы, эссе, новеллы, феерия", "Проза"),
+    ("extravaganza", "Феерия", "Проза"),
+    ("prose_abs", "Фантасмагория, абсурдистская проза", "Проза"),
+    ("comics", "Комиксы", "Прочее"),
+    ("unfinished", "Незавершенное", "Прочее"),
+    ("fanfiction", "Фанфик", "Прочее"),
+    (
+        "network_literature",
+        "Самиздат, сетевая литература",
+        "Прочее",
+    ),
+    (
+        "religion_christianity",
+        "Христианство",
+        "Религия, духовность, Эзотерика",
+    ),
+    (
+        "religion_orthodoxy",
+        "Православие",
+        "Религия, духовность, Эзотерика",
+    ),
+    (
+        "religion_protestantism",
+        "Протестантизм",
+        "Религия, духовность, Эзотерика",
+    ),
+    (
+        "religion_catholicism",
+        "Католицизм",
+        "Религия, духовность, Эзотерика",
+    ),
+    (
+        "religion_judaism",
+        "Иудаизм",
+        "Религия, духовность, Эзотерика",
+    ),
+    (
+        "religion_hinduism",
+        "Индуизм",
+        "Религия, духовность, Эзотерика",
+    ),
+    ("religion_islam", "Ислам", "Религия, духовность, Эзотерика"),
+    (
+        "religion_paganism",
+        "Язычество",
+        "Религия, духовность, Эзотерика",
+    ),
+    (
+        "astrology",
+        "Астрология и хиромантия",
+        "Религия, духовность, Эзотерика",
+    ),
+    (
+        "military_weapon",
+        "Военное дело, военная техника и вооружение",
+        "Техника",
+    ),
+    ("auto_business", "Автодело", "Техника"),
+    ("equ_history", "История техники", "Техника"),
+    ("sf_etc", "Фантастика", "Фантастика"),
+    ("russian_fantasy", "Славянское фэнтези", "Фантастика"),
+    ("sf_technofantasy", "Технофэнтези", "Фантастика"),
+    ("fairy_fantasy", "Мифологическое фэнтези", "Фантастика"),
+    ("hronoopera", "Хроноопера", "Фантастика"),
+    ("sf_mystic", "Мистика", "Фантастика"),
+    ("sf_stimpank", "Стимпанк", "Фантастика"),
+    ("modern_tale", "Современная сказка", "Фантастика"),
+    ("humor_satire", "Сатира", "Юмор"),
+    ("folklore", "Фольклор, загадки", "Фольклор"),
+    ("folklore_all", "Фольклор, загадки", "Фольклор"),
+    ("riddles", "Загадки", "Фольклор"),
+    ("folk_tale", "Народные сказки", "Фольклор"),
+    ("epic", "Былины, эпопея", "Фольклор"),
+    ("proverbs", "Пословицы, поговорки", "Фольклор"),
+    ("folk_songs", "Народные песни", "Фольклор"),
+    ("child_folklore", "Детский фольклор", "Фольклор"),
+    ("limerick", "Частушки, прибаутки, потешки", "Фольклор"),
+    ("music", "Музыка", "Искусство, Искусствоведение, Дизайн"),
+    (
+        "painting",
+        "Живопись, альбомы, иллюстрированные каталоги",
+        "Искусство, Искусствоведение, Дизайн",
+    ),
+    (
+        "architecture_book",
+        "Скульптура и архитектура",
+        "Искусство, Искусствоведение, Дизайн",
+    ),
+    (
+        "art_world_culture",
+        "Мировая художественная культура",
+        "Искусство, Искусствоведение, Дизайн",
+    ),
+    ("cine", "Кино", "Искусство, Искусствоведение, Дизайн"),
+    ("theatre", "Театр", "Искусство, Искусствоведение, Дизайн"),
+    (
+        "art_criticism",
+        "Искусствоведение",
+        "Искусство, Искусствоведение, Дизайн",
+    ),
+    ("sci_textbook", "Учебники и пособия", "Учебники и пособия"),
+    (
+        "tbg_school",
+        "Школьные учебники и пособия, рефераты, шпаргалки",
+        "Учебники и пособия",
+    ),
+    (
+        "tbg_secondary",
+        "Учебники и пособия для среднего и специального образования",
+        "Учебники и пособия",
+    ),
+    (
+        "tbg_higher",
+        "Учебники и пособия ВУЗов",
+        "Учебники и пособия",
+    ),
+    ("popadancy", "Попаданцы", "Фантастика"),
+    ("popadanec", "Попаданцы", "Фантастика"),
+    ("diafilm", "Диафильмы", "Прочее"),
+];
@@ -0,0 +1,426 @@
+use log::{debug, info};
+use rusqlite::{params, Connection};
+
+use std::{collections::HashMap, convert::TryFrom, path::Path};
+
+use crate::{genres::GENRES, schema, Error, Result};
+
+/// INP fields separator
+pub const SEPARATOR: char = '\u{4}';
+
+/// Author name as it stored in the INP record: `Last,First,Middle:`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Name {
+    pub first_name: String,
+    pub middle_name: String,
+    pub last_name: String,
+}
+impl From<&str> for Name {
+    fn from(name: &str) -> Self {
+        let mut parts = name.split(',');
+        let last_name = parts.next().unwrap_or_default().to_owned();
+        let first_name = parts.next().unwrap_or_default().to_owned();
+        let middle_name = parts.next().unwrap_or_default().to_owned();
+        Self {
+            first_name,
+            middle_name,
+            last_name,
+        }
+    }
+}
+
+/// Single book record of the INP catalogue
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Record {
+    pub authors: Vec<Name>,
+    pub genres: Vec<String>,
+    pub title: String,
+    pub serie: Option<String>,
+    pub serno: u32,
+    pub size: u32,
+    pub id: u32,
+    pub deleted: bool,
+    pub date: String,
+    pub lang: String,
+}
+impl TryFrom<&str> for Record {
+    type Error = Error;
+
+    fn try_from(line: &str) -> Result<Self> {
+        let fields = line.split(SEPARATOR).collect::<Vec<_>>();
+        if fields.len() < 12 {
+            return Err(Error::InvalidArgument(format!(
+                "INP record with {} fields: '{line}'",
+                fields.len()
+            )));
+        }
+
+        let number = |idx: usize| -> Result<u32> {
+            let field = fields[idx].trim();
+            field
+                .parse()
+                .map_err(|e| Error::InvalidArgument(format!("Field #{idx} '{field}': {e}")))
+        };
+        // Serie number and deletion mark are often left empty
+        let optional = |idx: usize| -> Result<u32> {
+            if fields[idx].trim().is_empty() {
+                Ok(0)
+            } else {
+                number(idx)
+            }
+        };
+        let id = number(7)?;
+        if id == 0 {
+            return Err(Error::InvalidArgument(format!("Book id 0: '{line}'")));
+        }
+
+        let authors = fields[0]
+            .split(':')
+            .filter(|s| !s.is_empty())
+            .map(Name::from)
+            .collect();
+        let genres = fields[1]
+            .split(':')
+            .filter(|s| !s.is_empty())
+            .map(String::from)
+            .collect();
+        let serie = Some(fields[3]).filter(|s| !s.is_empty()).map(String::from);
+
+        Ok(Self {
+            authors,
+            genres,
+            title: fields[2].to_owned(),
+            serie,
+            serno: optional(4)?,
+            size: number(6)?,
+            id,
+            deleted: optional(8)? != 0,
+            date: fields[10].to_owned(),
+            lang: fields[11].to_owned(),
+        })
+    }
+}
+
+/// Parses content of the INP file
+pub fn parse(content: &str) -> Result<Vec<Record>> {
+    content
+        .lines()
+        .filter(|line| !line.is_empty())
+        .map(Record::try_from)
+        .collect()
+}
+
+/// Value to id map of the dictionary table (titles, dates, series, ...)
+struct Dictionary {
+    table: &'static str,
+    ids: HashMap<String, u32>,
+    next: u32,
+}
+impl Dictionary {
+    fn load(conn: &Connection, table: &'static str) -> Result<Self> {
+        let mut statement = conn.prepare(&format!("SELECT id, value FROM {table}"))?;
+        let ids = statement
+            .query_map([], |row| Ok((row.get(1)?, row.get(0)?)))?
+            .collect::<rusqlite::Result<HashMap<String, u32>>>()?;
+        let next = ids.values().max().map_or(1, |id| id + 1);
+        Ok(Self { table, ids, next })
+    }
+
+    fn id(&mut self, conn: &Connection, value: &str) -> Result<u32> {
+        if let Some(id) = self.ids.get(value) {
+            return Ok(*id);
+        }
+        let id = self.next;
+        let sql = format!("INSERT INTO {}(id, value) VALUES ($1, $2)", self.table);
+        conn.prepare_cached(&sql)?.execute(params![id, value])?;
+        self.ids.insert(value.to_owned(), id);
+        self.next += 1;
+        Ok(id)
+    }
+}
+
+/// Imported INP file and the range of book ids it covers
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Import {
+    pub name: String,
+    pub first: u32,
+    pub last: u32,
+    pub count: u32,
+}
+impl Import {
+    /// Range of the archive by its name, e.g. `fb2-768381-769440.inp` or `.zip`
+    pub(crate) fn range(name: &str) -> Option<(u32, u32)> {
+        let stem = name.split('.').next()?;
+        let mut parts = stem.rsplitn(3, '-');
+        let last = parts.next()?.parse().ok()?;
+        let first = parts.next()?.parse().ok()?;
+        Some((first, last))
+    }
+}
+
+struct Importer<'a> {
+    conn: &'a Connection,
+    titles: Dictionary,
+    dates: Dictionary,
+    langs: Dictionary,
+    series: Dictionary,
+    genres: Dictionary,
+    first_names: Dictionary,
+    middle_names: Dictionary,
+    last_names: Dictionary,
+}
+impl<'a> Importer<'a> {
+    fn new(conn: &'a Connection) -> Result<Self> {
+        Self::create_schema(conn)?;
+        Ok(Self {
+            conn,
+            titles: Dictionary::load(conn, "titles")?,
+            dates: Dictionary::load(conn, "dates")?,
+            langs: Dictionary::load(conn, "langs")?,
+            series: Dictionary::load(conn, "series")?,
+            genres: Dictionary::load(conn, "genres")?,
+            first_names: Dictionary::load(conn, "first_names")?,
+            middle_names: Dictionary::load(conn, "middle_names")?,
+            last_names: Dictionary::load(conn, "last_names")?,
+        })
+    }
+
+    fn create_schema(conn: &Connection) -> Result<()> {
+        schema::migrate(conn)?;
+        let mut statement = conn
+            .prepare("INSERT OR IGNORE INTO genres_def(code, genre, meta) VALUES ($1, $2, $3)")?;
+        for (code, genre, meta) in GENRES {
+            statement.execute(params![code, genre, meta])?;
+        }
+        Ok(())
+    }
+
+    /// Adds the row of the new book, serie or author to the full-text index
+    fn index(
+        conn: &Connection,
+        name: &str,
+        kind: &str,
+        id: Option<u32>,
+        author: Option<(u32, u32, u32)>,
+    ) -> Result<()> {
+        let (fid, mid, lid) = match author {
+            Some((fid, mid, lid)) => (Some(fid), Some(mid), Some(lid)),
+            None => (None, None, None),
+        };
+        conn.prepare_cached(
+            "INSERT INTO search_index(name, kind, id, fid, mid, lid) VALUES ($1, $2, $3, $4, $5, $6)",
+        )?
+        .execute(params![name, kind, id, fid, mid, lid])?;
+        Ok(())
+    }
+
+    fn exists(&self, bid: u32) -> Result<bool> {
+        let mut statement = self
+            .conn
+            .prepare_cached("SELECT 1 FROM books WHERE book_id = $1")?;
+        Ok(statement.exists([bid])?)
+    }
+
+    fn record_import(&self, import: &Import) -> Result<()> {
+        self.conn
+            .prepare_cached(
+                r#"
+                INSERT INTO imports(name, first_id, last_id, books) VALUES ($1, $2, $3, $4)
+                ON CONFLICT(name) DO UPDATE SET books = books + excluded.books
+                "#,
+            )?
+            .execute(params![
+                import.name,
+                import.first,
+                import.last,
+                import.count
+            ])?;
+        Ok(())
+    }
+
+    fn insert(&mut self, record: &Record) -> Result<()> {
+        let conn = self.conn;
+        let bid = record.id;
+
+        for name in &record.authors {
+            let fid = self.first_names.id(conn, &name.first_name)?;
+            let mid = self.middle_names.id(conn, &name.middle_name)?;
+            let lid = self.last_names.id(conn, &name.last_name)?;
+            let known = conn
+                .prepare_cached(
+                    "SELECT 1 FROM authors_map WHERE last_name_id = $1 AND first_name_id = $2 AND middle_name_id = $3",
+                )?
+                .exists(params![lid, fid, mid])?;
+            if !known {
+                let full_name =
+                    format!("{} {} {}", name.first_name, name.middle_name, name.last_name);
+                Self::index(conn, full_name.trim(), "author", None, Some((fid, mid, lid)))?;
+            }
+            conn.prepare_cached(
+                "INSERT INTO authors_map(book_id, first_name_id, middle_name_id, last_name_id) VALUES ($1, $2, $3, $4)",
+            )?
+            .execute(params![bid, fid, mid, lid])?;
+        }
+
+        for genre in &record.genres {
+            let gid = self.genres.id(conn, genre)?;
+            conn.prepare_cached("INSERT INTO genres_map(book_id, genre_id) VALUES ($1, $2)")?
+                .execute(params![bid, gid])?;
+        }
+
+        let tid = self.titles.id(conn, &record.title)?;
+        Self::index(conn, &record.title, "book", Some(bid), None)?;
+        if let Some(serie) = &record.serie {
+            let sid = self.series.id(conn, serie)?;
+            let known = conn
+                .prepare_cached("SELECT 1 FROM series_map WHERE serie_id = $1")?
+                .exists([sid])?;
+            if !known {
+                Self::index(conn, serie, "serie", Some(sid), None)?;
+            }
+            conn.prepare_cached(
+                "INSERT INTO series_map(book_id, serie_id, serie_num) VALUES ($1, $2, $3)",
+            )?
+            .execute(params![bid, sid, record.serno])?;
+        }
+        let did = self.dates.id(conn, &record.date)?;
+        let lid = self.langs.id(conn, &record.lang)?;
+        conn.prepare_cached(
+            "INSERT INTO books(book_id, title_id, date_id, lang_id, book_size, deleted) VALUES ($1, $2, $3, $4, $5, $6)",
+        )?
+        .execute(params![bid, tid, did, lid, record.size, record.deleted])?;
+
+        Ok(())
+    }
+}
+
+/// Imports INP file into the database, returns number of imported books
+///
+/// The database may be empty or already contain the library: dictionary ids
+/// are reused, books which are already present are skipped and the file is
+/// recorded in the `imports` table.
+/// The caller is responsible for running it inside of a transaction.
+pub fn import<P: AsRef<Path>>(conn: &Connection, path: P) -> Result<usize> {
+    let path = path.as_ref();
+    debug!("import <- {}", path.display());
+
+    let content = std::fs::read_to_string(path)?;
+    let records = parse(&content)?;
+
+    let mut importer = Importer::new(conn)?;
+    let mut count = 0;
+    for record in &records {
+        if importer.exists(record.id)? {
+            debug!("skip existing book {}", record.id);
+        } else {
+            importer.insert(record)?;
+            count += 1;
+        }
+    }
+
+    let name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let ids = records.iter().map(|record| record.id);
+    let (first, last) = Import::range(&name)
+        .unwrap_or_else(|| (ids.clone().min().unwrap_or(0), ids.max().unwrap_or(0)));
+    importer.record_import(&Import {
+        name,
+        first,
+        last,
+        count: count as u32,
+    })?;
+
+    info!("{count} books imported from {}", path.display());
+    Ok(count)
+}
+
+/// Returns imported INP files ordered by the first book id
+pub fn imports(conn: &Connection) -> Result<Vec<Import>> {
+    let mut statement =
+        conn.prepare("SELECT name, first_id, last_id, books FROM imports ORDER BY first_id, name")?;
+    let rows = statement.query_map([], |row| {
+        Ok(Import {
+            name: row.get(0)?,
+            first: row.get(1)?,
+            last: row.get(2)?,
+            count: row.get(3)?,
+        })
+    })?;
+    Ok(rows.collect::<rusqlite::Result<Vec<_>>>()?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn record() -> anyhow::Result<()> {
+        let line = "Брин,К,Ф:Абэ,Джули,:\u{4}sf_fantasy:humor_fantasy:\u{4}Магическое свидание\u{4}Leveling Up\u{4}2\u{4}768383\u{4}2440356\u{4}768383\u{4}0\u{4}fb2\u{4}2024-06-01\u{4}ru\u{4}\u{4}магия\u{4}";
+        let record = Record::try_from(line)?;
+
+        assert_eq!(
+            record.authors,
+            vec![
+                Name {
+                    first_name: String::from("К"),
+                    middle_name: String::from("Ф"),
+                    last_name: String::from("Брин"),
+                },
+                Name {
+                    first_name: String::from("Джули"),
+                    middle_name: String::from(""),
+                    last_name: String::from("Абэ"),
+                }
+            ]
+        );
+        assert_eq!(record.genres, vec!["sf_fantasy", "humor_fantasy"]);
+        assert_eq!(record.title, "Магическое свидание");
+        assert_eq!(record.serie, Some(String::from("Leveling Up")));
+        assert_eq!(record.serno, 2);
+        assert_eq!(record.size, 2440356);
+        assert_eq!(record.id, 768383);
+        assert!(!record.deleted);
+        assert_eq!(record.date, "2024-06-01");
+        assert_eq!(record.lang, "ru");
+        Ok(())
+    }
+
+    #[test]
+    fn record_without_serie() -> anyhow::Result<()> {
+        let line = "Пайл,Говард,:\u{4}adv_history:\u{4}Рыцари\u{4}\u{4}\u{4}768409\u{4}1\u{4}768409\u{4}1\u{4}fb2\u{4}2024-06-01\u{4}ru";
+        let record = Record::try_from(line)?;
+
+        assert_eq!(record.serie, None);
+        assert_eq!(record.serno, 0);
+        assert!(record.deleted);
+        Ok(())
+    }
+
+    #[test]
+    fn import_range() {
+        assert_eq!(
+            Import::range("fb2-768381-769440.inp"),
+            Some((768381, 769440))
+        );
+        assert_eq!(Import::range("fb2-768381-769440"), Some((768381, 769440)));
+        assert_eq!(Import::range("catalog.inp"), None);
+    }
+
+    #[test]
+    fn record_bad_id() {
+        let line = |id: &str| {
+            format!("Пайл,Говард,:\u{4}adv_history:\u{4}Рыцари\u{4}\u{4}\u{4}{id}\u{4}1\u{4}{id}\u{4}1\u{4}fb2\u{4}2024-06-01\u{4}ru")
+        };
+        for id in ["", "0", "abc"] {
+            let result = Record::try_from(line(id).as_str());
+            assert!(matches!(result, Err(Error::InvalidArgument(_))), "{id}");
+        }
+    }
+
+    #[test]
+    fn record_too_short() {
+        assert!(Record::try_from("Пайл,Говард,:\u{4}adv_history:").is_err());
+    }
+}
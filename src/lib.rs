@@ -1,39 +1,147 @@
-use log::{debug, error};
-use queries::{Mapper, Query};
-use rusqlite::{functions::FunctionFlags, params, CachedStatement, Connection};
-
-use std::{convert::TryFrom, rc::Rc};
-
-pub use author::Author;
+use log::{debug, error, warn};
+use queries::{Array, PagedQuery, QueryParams, TypedQuery};
+use rusqlite::{functions::FunctionFlags, Connection};
+
+use std::{
+    collections::HashMap,
+    convert::TryFrom,
+    path::Path,
+    sync::{Arc, PoisonError, RwLock},
+};
+
+#[cfg(feature = "async")]
+pub use async_api::AsyncOpdsApi;
+pub use author::{Author, AuthorInfo};
 pub use book::Book;
+pub use date::Date;
+pub use error::{Error, Result};
+pub use found::Found;
+pub use genre::Genre;
+pub use page::{Page, PageRequest};
+pub use pool::OpdsPool;
 pub use serie::Serie;
 pub use value::Value;
 
+#[cfg(feature = "async")]
+pub mod async_api;
 pub mod author;
 pub mod book;
 pub mod collation;
+pub mod date;
+pub mod error;
+#[cfg(feature = "serde")]
+pub mod export;
+pub mod fb2;
+pub mod found;
+pub mod genre;
+pub mod importer;
+pub mod library;
+pub mod opds;
+pub mod opds2;
+pub mod page;
+pub mod pool;
 pub mod queries;
+pub mod router;
+pub mod schema;
 pub mod serie;
 pub mod value;
 
+mod genres;
+
 #[cfg(test)]
 mod api;
 
 #[derive(Debug)]
 pub struct OpdsApi {
     conn: Connection,
+    /// Ids of the accepted languages, all languages are accepted if None
+    languages: Arc<RwLock<Option<Vec<u32>>>>,
 }
 
 impl OpdsApi {
-    fn prepare(&self, query: &Query) -> anyhow::Result<CachedStatement> {
-        let sql = Query::get(query)?;
-        let statement = self.conn.prepare_cached(sql)?;
-        Ok(statement)
+    /// Runs the query and maps all of its rows
+    fn fetch<Q: TypedQuery>(&self, params: Q::Params) -> Result<Vec<Q::Row>> {
+        let mut statement = self.conn.prepare_cached(Q::SQL)?;
+        let rows = statement.query(params.values().as_slice())?.mapped(Q::map);
+        Ok(transfrom(rows)?)
     }
 
-    fn search_by_mask<F, S>(mask: S, fetcher: F) -> anyhow::Result<(Vec<String>, Vec<String>)>
+    /// Fetches the requested page of the list query and counts all of its rows
+    fn page<Q: PagedQuery>(&self, params: Q::Params, request: PageRequest) -> Result<Page<Q::Row>> {
+        let mut statement = self.conn.prepare_cached(Q::COUNT_SQL)?;
+        let total = statement.query_row(params.values().as_slice(), |row| row.get(0))?;
+
+        let items = self.slice::<Q>(params, request)?;
+        Ok(Page::new(items, total, request))
+    }
+
+    /// Fetches the requested slice of the list query without counting its rows
+    fn slice<Q: PagedQuery>(&self, params: Q::Params, request: PageRequest) -> Result<Vec<Q::Row>> {
+        let mut params = params.values();
+        let mut statement = self.conn.prepare_cached(Q::PAGE_SQL)?;
+        params.push(&request.limit);
+        params.push(&request.offset);
+        let rows = statement.query(params.as_slice())?.mapped(Q::map);
+        Ok(transfrom(rows)?)
+    }
+
+    /// Runs the query of the books and fills their authors
+    fn books<Q: TypedQuery<Row = Book>>(&self, params: Q::Params) -> Result<Vec<Book>> {
+        let books = self.fetch::<Q>(params)?;
+        self.with_authors(books)
+    }
+
+    /// Fetches the requested page of the books and fills their authors
+    fn books_page<Q>(&self, params: Q::Params, request: PageRequest) -> Result<Page<Book>>
     where
-        F: Fn(&String) -> anyhow::Result<Vec<String>>,
+        Q: PagedQuery<Row = Book>,
+    {
+        let mut page = self.page::<Q>(params, request)?;
+        page.items = self.with_authors(page.items)?;
+        Ok(page)
+    }
+
+    /// Fills the authors of the books
+    fn with_authors(&self, mut books: Vec<Book>) -> Result<Vec<Book>> {
+        let authors = self.authors_of_books(books.iter().map(|book| book.id).collect())?;
+        for book in books.iter_mut() {
+            book.authors = authors.get(&book.id).cloned().unwrap_or_default();
+        }
+        Ok(books)
+    }
+
+    /// Returns authors of the books by book id in the order of the INP record
+    fn authors_of_books(&self, ids: Vec<u32>) -> Result<HashMap<u32, Vec<Author>>> {
+        let mut res: HashMap<u32, Vec<Author>> = HashMap::new();
+        if ids.is_empty() {
+            return Ok(res);
+        }
+        for (book_id, author) in self.fetch::<queries::AuthorsOfBooksByIds>((Array(ids),))? {
+            res.entry(book_id).or_default().push(author);
+        }
+        Ok(res)
+    }
+
+    /// Returns next possible variants of the name by given prefix
+    fn next_chars<Q>(&self, prefix: &str) -> Result<Vec<String>>
+    where
+        Q: TypedQuery<Params = (u32, String), Row = String>,
+    {
+        let len = (prefix.chars().count() + 1) as u32;
+        let matcher = format!(
+            "{}*",
+            prefix
+                .replace("[", "?")
+                .replace("]", "?")
+                .replace("*", "?")
+                .to_lowercase()
+        );
+        self.fetch::<Q>((len, matcher))
+    }
+
+    fn search_by_mask<F, S>(mask: S, fetcher: F) -> Result<(Vec<String>, Vec<String>)>
+    where
+        F: Fn(&String) -> Result<Vec<String>>,
         S: Into<String>,
     {
         let mut mask = mask.into();
@@ -70,19 +178,261 @@ impl OpdsApi {
 
     /// Create OpdsApi instance
     pub fn new(conn: Connection) -> Self {
-        OpdsApi { conn }
+        OpdsApi {
+            conn,
+            languages: Arc::default(),
+        }
+    }
+
+    /// Registers the collations, SQL functions and modules the queries rely on
+    pub(crate) fn with_functions(conn: Connection) -> Result<Self> {
+        conn.create_collation("opds", collation::collation)?;
+        conn.create_collation("opds_ci", collation::collation_ci)?;
+        conn.create_collation("opds_natural", collation::collation_natural)?;
+
+        let flags = FunctionFlags::SQLITE_DETERMINISTIC;
+        conn.create_scalar_function("LOWER", 1, flags, |ctx| {
+            ctx.get::<String>(0).map(|s| s.to_lowercase())
+        })?;
+        rusqlite::vtab::array::load_module(&conn)?;
+
+        let languages: Arc<RwLock<Option<Vec<u32>>>> = Arc::default();
+        let filter = Arc::clone(&languages);
+        let flags = FunctionFlags::SQLITE_UTF8;
+        conn.create_scalar_function("opds_lang", 1, flags, move |ctx| {
+            let id = ctx.get::<u32>(0)?;
+            let languages = filter.read().unwrap_or_else(PoisonError::into_inner);
+            Ok(languages.as_ref().is_none_or(|ids| ids.contains(&id)))
+        })?;
+        Ok(OpdsApi { conn, languages })
+    }
+
+    /// Returns the schema version of the database, `PRAGMA user_version`
+    pub fn schema_version(&self) -> Result<u32> {
+        schema::version(&self.conn)
+    }
+
+    /// Returns the state of the database schema compared to [schema::VERSION]
+    pub fn schema_status(&self) -> Result<schema::Status> {
+        schema::status(&self.conn)
+    }
+
+    /// Opens the database for writing and upgrades its schema to [schema::VERSION]
+    ///
+    /// Opening with [TryFrom] never changes the schema, the upgrade is opt-in.
+    pub fn open_migrated(database: &str) -> Result<Self> {
+        debug!("open_migrated <- {database}");
+
+        let conn = Connection::open(database).inspect_err(|e| error!("{e}"))?;
+        let mut api = OpdsApi::with_functions(conn)?;
+        api.migrate()?;
+        Ok(api)
+    }
+
+    /// Upgrades the database schema in place, returns the previous version
+    ///
+    /// Runs all pending migrations in a single transaction.
+    pub fn migrate(&mut self) -> Result<u32> {
+        debug!("migrate <- {}", self.schema_version()?);
+
+        let tx = self.conn.transaction()?;
+        let version = schema::migrate(&tx)?;
+        tx.commit()?;
+        Ok(version)
+    }
+
+    /// Refuses the outdated database, accepts the newer one only if all queries still work
+    ///
+    /// The outdated database must be upgraded with [OpdsApi::open_migrated] first. The newer
+    /// one is read as is, it is never migrated or imported into.
+    pub(crate) fn check_schema(&self) -> Result<()> {
+        let supported = schema::VERSION;
+        match self.schema_status()? {
+            schema::Status::Empty | schema::Status::Current => Ok(()),
+            schema::Status::Outdated(version) => {
+                let failure = format!(
+                    "schema is older than version {supported}, \
+                    upgrade it with open_migrated or `opds_server --migrate`"
+                );
+                error!("Schema version {version}: {failure}");
+                Err(Error::Schema { version, failures: vec![failure] })
+            }
+            schema::Status::Unknown(version) => {
+                warn!("Schema version {version} is newer than {supported}, reading it as is");
+                self.validate().map(|_| ())
+            }
+        }
+    }
+
+    /// Returns true if the database has no tables yet, e.g. before the first import
+    pub fn is_empty(&self) -> Result<bool> {
+        Ok(!schema::has_tables(&self.conn)?)
+    }
+
+    /// Prepares every query and checks it returns the columns its row mapper reads
+    ///
+    /// All failures are reported at once with the detected schema version
+    pub fn validate(&self) -> Result<u32> {
+        let version = self.schema_version()?;
+        debug!("validate <- schema version {version}");
+
+        let mut failures = Vec::new();
+        for query in queries::QUERIES {
+            match self.conn.prepare(query.sql) {
+                Ok(statement) => {
+                    let names = statement.column_names();
+                    let missing = query
+                        .columns
+                        .iter()
+                        .filter(|column| !names.contains(column))
+                        .copied()
+                        .collect::<Vec<_>>();
+                    if !missing.is_empty() {
+                        failures.push(format!("{} misses {}", query.name, missing.join(", ")));
+                    }
+                }
+                Err(e) => failures.push(format!("{}: {e}", query.name)),
+            }
+        }
+        if failures.is_empty() {
+            Ok(version)
+        } else {
+            failures.iter().for_each(|failure| error!("{failure}"));
+            Err(Error::Schema { version, failures })
+        }
+    }
+
+    /// Imports INP catalogue file into the database, returns number of imported books
+    ///
+    /// Books which are already in the database are skipped, so the method
+    /// applies daily INP deltas to the existing library. The import runs in
+    /// a single transaction and leaves the database untouched on failure.
+    pub fn import_inp<P: AsRef<Path>>(&mut self, path: P) -> Result<usize> {
+        debug!("import_inp <- {}", path.as_ref().display());
+
+        let tx = self.conn.transaction()?;
+        let count = importer::import(&tx, path)?;
+        tx.commit()?;
+        Ok(count)
+    }
+
+    /// Restricts books, series and authors to the books in the languages, e.g. `["uk", "en"]`
+    ///
+    /// The empty list accepts all languages again. Unknown codes are refused
+    /// with [Error::InvalidArgument] and the previous languages are kept.
+    pub fn set_languages<S: AsRef<str>>(&self, codes: &[S]) -> Result<()> {
+        let codes = codes
+            .iter()
+            .map(|code| code.as_ref().to_lowercase())
+            .collect::<Vec<_>>();
+        debug!("set_languages <- {codes:?}");
+
+        let ids = if codes.is_empty() {
+            None
+        } else {
+            let langs = self.fetch::<queries::LanguagesByCodes>((Array(codes.clone()),))?;
+            let unknown = codes
+                .iter()
+                .filter(|code| !langs.iter().any(|lang| lang.value.to_lowercase() == **code))
+                .map(String::as_str)
+                .collect::<Vec<_>>();
+            if !unknown.is_empty() {
+                let unknown = unknown.join(", ");
+                return Err(Error::InvalidArgument(format!("Unknown languages: {unknown}")));
+            }
+            Some(langs.into_iter().map(|lang| lang.id).collect())
+        };
+        self.set_language_ids(ids);
+        Ok(())
+    }
+
+    /// Returns ids of the accepted languages, None if all languages are accepted
+    pub(crate) fn language_ids(&self) -> Option<Vec<u32>> {
+        self.languages
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+
+    /// Replaces the ids of the accepted languages
+    pub(crate) fn set_language_ids(&self, ids: Option<Vec<u32>>) {
+        // The filter is replaced as a whole, so the poisoned lock still holds the valid one
+        *self
+            .languages
+            .write()
+            .unwrap_or_else(PoisonError::into_inner) = ids;
+    }
+
+    /// Returns language codes with the number of books, most used first
+    pub fn languages(&self) -> Result<Vec<(String, u32)>> {
+        debug!("languages <- ");
+
+        self.fetch::<queries::Languages>(())
+    }
+
+    /// Returns INP files imported into the database
+    pub fn imports(&self) -> Result<Vec<importer::Import>> {
+        debug!("imports <- ");
+
+        importer::imports(&self.conn)
     }
 
     /// Returns true if database opened in ReadOnly
-    pub fn is_readonly(&self) -> anyhow::Result<bool> {
+    pub fn is_readonly(&self) -> Result<bool> {
         Ok(self.conn.is_readonly(rusqlite::DatabaseName::Main)?)
     }
 
+    /// Returns authors, series and books ranked by the full-text match of the query
+    ///
+    /// Every word of the query matches as a prefix, so "заков" finds
+    /// "Рыцари, закованные в сталь". The limit applies to the matched items.
+    pub fn search(&self, query: &str, limit: u32) -> Result<Vec<Found>> {
+        debug!("search <- {query}, {limit}");
+
+        let pattern = fts_pattern(query);
+        if pattern.is_empty() {
+            return Ok(Vec::new());
+        }
+        let found = self.slice::<queries::Search>((pattern,), PageRequest::new(0, limit))?;
+        self.with_found_authors(found)
+    }
+
+    /// Returns the page of authors, series and books ranked by the full-text match of the query
+    pub fn search_page(&self, query: &str, page: PageRequest) -> Result<Page<Found>> {
+        debug!("search_page <- {query}, {page:?}");
+
+        let pattern = fts_pattern(query);
+        if pattern.is_empty() {
+            return Ok(Page::new(Vec::new(), 0, page));
+        }
+        let mut page = self.page::<queries::Search>((pattern,), page)?;
+        page.items = self.with_found_authors(page.items)?;
+        Ok(page)
+    }
+
+    /// Fills the authors of the found books
+    fn with_found_authors(&self, mut found: Vec<Found>) -> Result<Vec<Found>> {
+        let ids = found
+            .iter()
+            .filter_map(|found| match found {
+                Found::Book(book) => Some(book.id),
+                _ => None,
+            })
+            .collect();
+        let authors = self.authors_of_books(ids)?;
+        for found in found.iter_mut() {
+            if let Found::Book(book) = found {
+                book.authors = authors.get(&book.id).cloned().unwrap_or_default();
+            }
+        }
+        Ok(found)
+    }
+
     /// Returns Authors and NVC of the author name by given prefix
     pub fn search_authors_by_prefix(
         &self,
         prefix: &String,
-    ) -> anyhow::Result<(Vec<String>, Vec<String>)> {
+    ) -> Result<(Vec<String>, Vec<String>)> {
         debug!("search_authors_by_prefix <- {prefix}");
 
         let fetcher = |s: &String| self.authors_next_char_by_prefix(s);
@@ -90,82 +440,31 @@ impl OpdsApi {
     }
 
     /// Returns next possible variants of the author name by given prefix
-    pub fn authors_next_char_by_prefix(&self, prefix: &String) -> anyhow::Result<Vec<String>> {
+    pub fn authors_next_char_by_prefix(&self, prefix: &String) -> Result<Vec<String>> {
         debug!("authors_next_char_by_prefix <- {prefix}");
 
-        let len = (prefix.chars().count() + 1) as u32;
-        let query = Query::AuthorNextCharByPrefix;
-        if let Mapper::String(mapper) = Query::mapper(&query) {
-            let mut statement = self.prepare(&query)?;
-            let matcher = format!(
-                "{}*",
-                prefix
-                    .replace("[", "?")
-                    .replace("]", "?")
-                    .replace("*", "?")
-                    .to_lowercase()
-            );
-            let rows = statement.query(params![len, matcher])?.mapped(mapper);
-            let res = transfrom(rows)?;
-            Ok(res)
-        } else {
-            Err(anyhow::anyhow!("Unexpected mapper"))
-        }
+        self.next_chars::<queries::AuthorNextCharByPrefix>(prefix)
     }
 
     /// Returns next possible variants of the serie name by given prefix
-    pub fn series_next_char_by_prefix(&self, prefix: &String) -> anyhow::Result<Vec<String>> {
+    pub fn series_next_char_by_prefix(&self, prefix: &String) -> Result<Vec<String>> {
         debug!("series_next_char_by_prefix <- {prefix}");
 
-        let len = (prefix.chars().count() + 1) as u32;
-        let query = Query::SerieNextCharByPrefix;
-        if let Mapper::String(mapper) = Query::mapper(&query) {
-            let mut statement = self.prepare(&query)?;
-            let matcher = format!(
-                "{}*",
-                prefix
-                    .replace("[", "?")
-                    .replace("]", "?")
-                    .replace("*", "?")
-                    .to_lowercase()
-            );
-            let rows = statement.query(params![len, matcher])?.mapped(mapper);
-            let res = transfrom(rows)?;
-            Ok(res)
-        } else {
-            Err(anyhow::anyhow!("Unexpected mapper"))
-        }
+        self.next_chars::<queries::SerieNextCharByPrefix>(prefix)
     }
 
     /// Returns next possible variants of the serie name by given prefix
-    pub fn books_next_char_by_prefix(&self, prefix: &String) -> anyhow::Result<Vec<String>> {
+    pub fn books_next_char_by_prefix(&self, prefix: &String) -> Result<Vec<String>> {
         debug!("books_next_char_by_prefix <- {prefix}");
 
-        let len = (prefix.chars().count() + 1) as u32;
-        let query = Query::BookNextCharByPrefix;
-        if let Mapper::String(mapper) = Query::mapper(&query) {
-            let mut statement = self.prepare(&query)?;
-            let matcher = format!(
-                "{}*",
-                prefix
-                    .replace("[", "?")
-                    .replace("]", "?")
-                    .replace("*", "?")
-                    .to_lowercase()
-            );
-            let rows = statement.query(params![len, matcher])?.mapped(mapper);
-            let res = transfrom(rows)?;
-            Ok(res)
-        } else {
-            Err(anyhow::anyhow!("Unexpected mapper"))
-        }
+        self.next_chars::<queries::BookNextCharByPrefix>(prefix)
     }
 
     /// Returns NVC of the serie name by given prefix
     pub fn search_series_by_prefix(
         &self,
         prefix: &String,
-    ) -> anyhow::Result<(Vec<String>, Vec<String>)> {
+    ) -> Result<(Vec<String>, Vec<String>)> {
         debug!("search_series_by_prefix <- {prefix}");
 
         let fetcher = |s: &String| self.series_next_char_by_prefix(s);
@@ -176,7 +475,7 @@ impl OpdsApi {
     pub fn search_books_by_prefix(
         &self,
         prefix: &String,
-    ) -> anyhow::Result<(Vec<String>, Vec<String>)> {
+    ) -> Result<(Vec<String>, Vec<String>)> {
         debug!("search_books_by_prefix <- {prefix}");
 
         let fetcher = |s: &String| self.books_next_char_by_prefix(s);
@@ -184,157 +483,183 @@ impl OpdsApi {
     }
 
     /// Returns Authors by exact last name
-    pub fn authors_by_last_name(&self, name: &String) -> anyhow::Result<Vec<Author>> {
+    pub fn authors_by_last_name(&self, name: &String) -> Result<Vec<AuthorInfo>> {
         debug!("authors_by_last_name <- {name}");
 
-        let query = Query::AuthorsByLastName;
-        if let Mapper::Author(mapper) = Query::mapper(&query) {
-            let mut statement = self.prepare(&query)?;
-            let rows = statement.query([name.to_lowercase()])?.mapped(mapper);
-            let res = transfrom(rows)?;
-            Ok(res)
-        } else {
-            Err(anyhow::anyhow!("Unexpected mapper"))
-        }
+        self.fetch::<queries::AuthorsByLastName>((name.to_lowercase(),))
+    }
+
+    /// Returns the page of Authors by exact last name
+    pub fn authors_by_last_name_page(
+        &self,
+        name: &String,
+        page: PageRequest,
+    ) -> Result<Page<AuthorInfo>> {
+        debug!("authors_by_last_name_page <- {name}, {page:?}");
+
+        self.page::<queries::AuthorsByLastName>((name.to_lowercase(),), page)
     }
 
     /// Returns Authors by Genre name
-    pub fn authors_by_genre_id(&self, gid: u32) -> anyhow::Result<Vec<Author>> {
+    pub fn authors_by_genre_id(&self, gid: u32) -> Result<Vec<AuthorInfo>> {
         debug!("authors_by_genre_id <- {gid}");
 
-        let query = Query::AuthorsByGenreId;
-        if let Mapper::Author(mapper) = Query::mapper(&query) {
-            let mut statement = self.prepare(&query)?;
-            let rows = statement.query([gid])?.mapped(mapper);
-            let res = transfrom(rows)?;
-            Ok(res)
-        } else {
-            Err(anyhow::anyhow!("Unexpected mapper"))
-        }
+        self.fetch::<queries::AuthorsByGenreId>((gid,))
+    }
+
+    /// Returns the page of Authors by Genre id
+    pub fn authors_by_genre_id_page(
+        &self,
+        gid: u32,
+        page: PageRequest,
+    ) -> Result<Page<AuthorInfo>> {
+        debug!("authors_by_genre_id_page <- {gid}, {page:?}");
+
+        self.page::<queries::AuthorsByGenreId>((gid,), page)
     }
 
     /// Returns Authors by Genre name
-    pub fn authors_by_books_ids(&self, ids: Vec<u32>) -> anyhow::Result<Vec<Author>> {
+    pub fn authors_by_books_ids(&self, ids: Vec<u32>) -> Result<Vec<Author>> {
         debug!("authors_by_books_ids <- {:?}", ids);
 
-        let query = Query::AuthorsByBooksIds;
-        if let Mapper::Author(mapper) = Query::mapper(&query) {
-            let mut statement = self.prepare(&query)?;
-            use rusqlite::types::Value;
-            let params = Rc::new(ids.into_iter().map(Value::from).collect::<Vec<Value>>());
-            let rows = statement.query(params![params])?.mapped(mapper);
-            let res = transfrom(rows)?;
-            Ok(res)
-        } else {
-            Err(anyhow::anyhow!("Unexpected mapper"))
-        }
+        self.fetch::<queries::AuthorsByBooksIds>((Array(ids),))
     }
 
     /// Returns Series by series ids
-    pub fn series_by_ids(&self, ids: Vec<u32>) -> anyhow::Result<Vec<Serie>> {
+    pub fn series_by_ids(&self, ids: Vec<u32>) -> Result<Vec<Serie>> {
         debug!("series_by_serie_name <- {:?}", ids);
 
-        let query = Query::SeriesByIds;
-        if let Mapper::Serie(mapper) = Query::mapper(&query) {
-            let mut statement = self.prepare(&query)?;
-            use rusqlite::types::Value;
-            let params = Rc::new(ids.into_iter().map(Value::from).collect::<Vec<Value>>());
-            let rows = statement.query(params![params])?.mapped(mapper);
-            let res = transfrom(rows)?;
-            Ok(res)
-        } else {
-            Err(anyhow::anyhow!("Unexpected mapper"))
-        }
+        self.fetch::<queries::SeriesByIds>((Array(ids),))
     }
 
     /// Returns Series by exact serie name
-    pub fn series_by_serie_name(&self, name: &String) -> anyhow::Result<Vec<Serie>> {
+    pub fn series_by_serie_name(&self, name: &String) -> Result<Vec<Serie>> {
         debug!("series_by_serie_name <- {name}");
 
-        let query = Query::SeriesBySerieName;
-        if let Mapper::Serie(mapper) = Query::mapper(&query) {
-            let mut statement = self.prepare(&query)?;
-            let rows = statement.query([name])?.mapped(mapper);
-            let res = transfrom(rows)?;
-            Ok(res)
-        } else {
-            Err(anyhow::anyhow!("Unexpected mapper"))
-        }
+        self.fetch::<queries::SeriesBySerieName>((name.clone(),))
+    }
+
+    /// Returns the page of Series by exact serie name
+    pub fn series_by_serie_name_page(
+        &self,
+        name: &String,
+        page: PageRequest,
+    ) -> Result<Page<Serie>> {
+        debug!("series_by_serie_name_page <- {name}, {page:?}");
+
+        self.page::<queries::SeriesBySerieName>((name.clone(),), page)
     }
 
     /// Returns Series by Genre name
-    pub fn series_by_genre_id(&self, gid: u32) -> anyhow::Result<Vec<Serie>> {
+    pub fn series_by_genre_id(&self, gid: u32) -> Result<Vec<Serie>> {
         debug!("series_by_genre_id <- {gid}");
 
-        let query = Query::SeriesByGenreId;
-        if let Mapper::Serie(mapper) = Query::mapper(&query) {
-            let mut statement = self.prepare(&query)?;
-            let rows = statement.query([gid])?.mapped(mapper);
-            let res = transfrom(rows)?;
-            Ok(res)
-        } else {
-            Err(anyhow::anyhow!("Unexpected mapper"))
-        }
+        self.fetch::<queries::SeriesByGenreId>((gid,))
+    }
+
+    /// Returns the page of Series by Genre id
+    pub fn series_by_genre_id_page(
+        &self,
+        gid: u32,
+        page: PageRequest,
+    ) -> Result<Page<Serie>> {
+        debug!("series_by_genre_id_page <- {gid}, {page:?}");
+
+        self.page::<queries::SeriesByGenreId>((gid,), page)
     }
 
     /// Returns Series by authors ids
-    pub fn series_by_author_ids(&self, fid: u32, mid: u32, lid: u32) -> anyhow::Result<Vec<Serie>> {
+    pub fn series_by_author_ids(&self, fid: u32, mid: u32, lid: u32) -> Result<Vec<Serie>> {
         debug!("series_by_author_ids <- {fid}, {mid}, {lid}");
 
-        let query = Query::SeriesByAuthorIds;
-        if let Mapper::Serie(mapper) = Query::mapper(&query) {
-            let mut statement = self.prepare(&query)?;
-            let rows = statement.query([fid, mid, lid])?.mapped(mapper);
-            let res = transfrom(rows)?;
-            Ok(res)
-        } else {
-            Err(anyhow::anyhow!("Unexpected mapper"))
-        }
+        self.fetch::<queries::SeriesByAuthorIds>((fid, mid, lid))
+    }
+
+    /// Returns the page of Series by authors ids
+    pub fn series_by_author_ids_page(
+        &self,
+        fid: u32,
+        mid: u32,
+        lid: u32,
+        page: PageRequest,
+    ) -> Result<Page<Serie>> {
+        debug!("series_by_author_ids_page <- {fid}, {mid}, {lid}, {page:?}");
+
+        self.page::<queries::SeriesByAuthorIds>((fid, mid, lid), page)
     }
 
     /// Returns Author by ids
-    pub fn author_by_ids(&self, fid: u32, mid: u32, lid: u32) -> anyhow::Result<Option<Author>> {
+    pub fn author_by_ids(&self, fid: u32, mid: u32, lid: u32) -> Result<Option<Author>> {
         debug!("author_by_ids <- {fid}, {mid}, {lid}");
 
-        let query = Query::AuthorByIds;
-        if let Mapper::Author(mapper) = Query::mapper(&query) {
-            let mut statement = self.prepare(&query)?;
-            let rows = statement.query([fid, mid, lid])?.mapped(mapper);
-            let res = transfrom(rows)?;
-            Ok(res.first().cloned())
-        } else {
-            Err(anyhow::anyhow!("Unexpected mapper"))
-        }
+        let res = self.fetch::<queries::AuthorByIds>((fid, mid, lid))?;
+        Ok(res.into_iter().next())
     }
 
     /// Returns Book by id
-    pub fn book_by_id(&self, bid: u32) -> anyhow::Result<Option<Book>> {
+    pub fn book_by_id(&self, bid: u32) -> Result<Option<Book>> {
         debug!("book_by_id <- {bid}");
 
-        let query = Query::BookById;
-        if let Mapper::Book(mapper) = Query::mapper(&query) {
-            let mut statement = self.prepare(&query)?;
-            let rows = statement.query([bid])?.mapped(mapper);
-            let res = transfrom(rows)?;
-            Ok(res.first().cloned())
-        } else {
-            Err(anyhow::anyhow!("Unexpected mapper"))
-        }
+        let res = self.books::<queries::BookById>((bid,))?;
+        Ok(res.into_iter().next())
     }
 
     /// Returns book by Author by ids
-    pub fn books_by_author_ids(&self, fid: u32, mid: u32, lid: u32) -> anyhow::Result<Vec<Book>> {
+    pub fn books_by_author_ids(&self, fid: u32, mid: u32, lid: u32) -> Result<Vec<Book>> {
         debug!("books_by_author_ids <- {fid}, {mid}, {lid}");
 
-        let query = Query::BooksByAuthorIds;
-        if let Mapper::Book(mapper) = Query::mapper(&query) {
-            let mut statement = self.prepare(&query)?;
-            let rows = statement.query([fid, mid, lid])?.mapped(mapper);
-            let res = transfrom(rows)?;
-            Ok(res)
-        } else {
-            Err(anyhow::anyhow!("Unexpected mapper"))
-        }
+        self.books::<queries::BooksByAuthorIds>((fid, mid, lid, false))
+    }
+
+    /// Returns the page of books by Author ids
+    pub fn books_by_author_ids_page(
+        &self,
+        fid: u32,
+        mid: u32,
+        lid: u32,
+        page: PageRequest,
+    ) -> Result<Page<Book>> {
+        debug!("books_by_author_ids_page <- {fid}, {mid}, {lid}, {page:?}");
+
+        self.books_page::<queries::BooksByAuthorIds>((fid, mid, lid, false), page)
+    }
+
+    /// Returns deleted books by Author by ids
+    pub fn deleted_books_by_author_ids(
+        &self,
+        fid: u32,
+        mid: u32,
+        lid: u32,
+    ) -> Result<Vec<Book>> {
+        debug!("deleted_books_by_author_ids <- {fid}, {mid}, {lid}");
+
+        self.books::<queries::BooksByAuthorIds>((fid, mid, lid, true))
+    }
+
+    /// Returns the page of deleted books by Author ids
+    pub fn deleted_books_by_author_ids_page(
+        &self,
+        fid: u32,
+        mid: u32,
+        lid: u32,
+        page: PageRequest,
+    ) -> Result<Page<Book>> {
+        debug!("deleted_books_by_author_ids_page <- {fid}, {mid}, {lid}, {page:?}");
+
+        self.books_page::<queries::BooksByAuthorIds>((fid, mid, lid, true), page)
+    }
+
+    /// Returns count of deleted books by Author by ids
+    pub fn deleted_books_count_by_author_ids(
+        &self,
+        fid: u32,
+        mid: u32,
+        lid: u32,
+    ) -> Result<u32> {
+        debug!("deleted_books_count_by_author_ids <- {fid}, {mid}, {lid}");
+
+        let res = self.fetch::<queries::BooksCountByAuthorIds>((fid, mid, lid, true))?;
+        Ok(res.into_iter().next().ok_or(rusqlite::Error::QueryReturnedNoRows)?)
     }
 
     /// Returns book by Author by ids and Serie id
@@ -344,27 +669,24 @@ impl OpdsApi {
         mid: u32,
         lid: u32,
         sid: u32,
-    ) -> anyhow::Result<Vec<Book>> {
+    ) -> Result<Vec<Book>> {
         debug!("books_by_author_ids_and_serie_id <- {fid}, {mid}, {lid}, {sid}");
 
-        let query = Query::BooksByAuthorIds;
-        if let Mapper::Book(mapper) = Query::mapper(&query) {
-            let mut statement = self.prepare(&query)?;
-            let rows = statement.query([fid, mid, lid])?.mapped(mapper);
-            let res = transfrom(rows)?
-                .into_iter()
-                .filter(|book| {
-                    if let Some(serie_id) = book.sid {
-                        serie_id == sid
-                    } else {
-                        false
-                    }
-                })
-                .collect();
-            Ok(res)
-        } else {
-            Err(anyhow::anyhow!("Unexpected mapper"))
-        }
+        self.books::<queries::BooksByAuthorIdsAndSerieId>((fid, mid, lid, Some(sid)))
+    }
+
+    /// Returns the page of books by Author ids and Serie id
+    pub fn books_by_author_ids_and_serie_id_page(
+        &self,
+        fid: u32,
+        mid: u32,
+        lid: u32,
+        sid: u32,
+        page: PageRequest,
+    ) -> Result<Page<Book>> {
+        debug!("books_by_author_ids_and_serie_id_page <- {fid}, {mid}, {lid}, {sid}, {page:?}");
+
+        self.books_page::<queries::BooksByAuthorIdsAndSerieId>((fid, mid, lid, Some(sid)), page)
     }
 
     /// Returns book by Author by ids without Serie
@@ -373,125 +695,246 @@ impl OpdsApi {
         fid: u32,
         mid: u32,
         lid: u32,
-    ) -> anyhow::Result<Vec<Book>> {
+    ) -> Result<Vec<Book>> {
         debug!("books_by_author_ids_without_serie <- {fid}, {mid}, {lid}");
 
-        let query = Query::BooksByAuthorIds;
-        if let Mapper::Book(mapper) = Query::mapper(&query) {
-            let mut statement = self.prepare(&query)?;
-            let rows = statement.query([fid, mid, lid])?.mapped(mapper);
-            let res = transfrom(rows)?
-                .into_iter()
-                .filter(|book| book.sid.is_none())
-                .collect();
-            Ok(res)
-        } else {
-            Err(anyhow::anyhow!("Unexpected mapper"))
-        }
+        self.books::<queries::BooksByAuthorIdsAndSerieId>((fid, mid, lid, None))
+    }
+
+    /// Returns the page of books by Author ids without Serie
+    pub fn books_by_author_ids_without_serie_page(
+        &self,
+        fid: u32,
+        mid: u32,
+        lid: u32,
+        page: PageRequest,
+    ) -> Result<Page<Book>> {
+        debug!("books_by_author_ids_without_serie_page <- {fid}, {mid}, {lid}, {page:?}");
+
+        self.books_page::<queries::BooksByAuthorIdsAndSerieId>((fid, mid, lid, None), page)
     }
 
     /// Returns book by Serie id
-    pub fn books_by_serie_id(&self, sid: u32) -> anyhow::Result<Vec<Book>> {
+    pub fn books_by_serie_id(&self, sid: u32) -> Result<Vec<Book>> {
         debug!("books_by_serie_id <- {sid}");
 
-        let query = Query::BooksBySerieId;
-        if let Mapper::Book(mapper) = Query::mapper(&query) {
-            let mut statement = self.prepare(&query)?;
-            let rows = statement.query([sid])?.mapped(mapper);
-            let res = transfrom(rows)?;
-            Ok(res)
-        } else {
-            Err(anyhow::anyhow!("Unexpected mapper"))
-        }
+        self.books::<queries::BooksBySerieId>((sid, false))
+    }
+
+    /// Returns the page of books by Serie id
+    pub fn books_by_serie_id_page(
+        &self,
+        sid: u32,
+        page: PageRequest,
+    ) -> Result<Page<Book>> {
+        debug!("books_by_serie_id_page <- {sid}, {page:?}");
+
+        self.books_page::<queries::BooksBySerieId>((sid, false), page)
+    }
+
+    /// Returns deleted books by Serie id
+    pub fn deleted_books_by_serie_id(&self, sid: u32) -> Result<Vec<Book>> {
+        debug!("deleted_books_by_serie_id <- {sid}");
+
+        self.books::<queries::BooksBySerieId>((sid, true))
+    }
+
+    /// Returns the page of deleted books by Serie id
+    pub fn deleted_books_by_serie_id_page(
+        &self,
+        sid: u32,
+        page: PageRequest,
+    ) -> Result<Page<Book>> {
+        debug!("deleted_books_by_serie_id_page <- {sid}, {page:?}");
+
+        self.books_page::<queries::BooksBySerieId>((sid, true), page)
+    }
+
+    /// Returns count of deleted books by Serie id
+    pub fn deleted_books_count_by_serie_id(&self, sid: u32) -> Result<u32> {
+        debug!("deleted_books_count_by_serie_id <- {sid}");
+
+        let res = self.fetch::<queries::BooksCountBySerieId>((sid, true))?;
+        Ok(res.into_iter().next().ok_or(rusqlite::Error::QueryReturnedNoRows)?)
     }
 
     /// Returns book by Genre id and date filter
-    pub fn books_by_genre_id_and_date(&self, gid: u32, date: String) -> anyhow::Result<Vec<Book>> {
+    pub fn books_by_genre_id_and_date(&self, gid: u32, date: String) -> Result<Vec<Book>> {
         debug!("books_by_genre_id_and_date <- {gid}, {date}");
 
-        let query = Query::BooksByGenreIdAndDate;
-        if let Mapper::Book(mapper) = Query::mapper(&query) {
-            let mut statement = self.prepare(&query)?;
-            let rows = statement.query(params![gid, date])?.mapped(mapper);
-            let res = transfrom(rows)?;
-            Ok(res)
-        } else {
-            Err(anyhow::anyhow!("Unexpected mapper"))
+        self.books::<queries::BooksByGenreIdAndDate>((gid, date))
+    }
+
+    /// Returns the page of books by Genre id and date filter
+    pub fn books_by_genre_id_and_date_page(
+        &self,
+        gid: u32,
+        date: String,
+        page: PageRequest,
+    ) -> Result<Page<Book>> {
+        debug!("books_by_genre_id_and_date_page <- {gid}, {date}, {page:?}");
+
+        self.books_page::<queries::BooksByGenreIdAndDate>((gid, date), page)
+    }
+
+    /// Returns books added from `from` to `to` inclusive, the newest first
+    pub fn books_added_between(&self, from: Date, to: Date) -> Result<Vec<Book>> {
+        debug!("books_added_between <- {from}, {to}");
+
+        self.books::<queries::BooksAddedBetween>((from, to))
+    }
+
+    /// Returns the page of books added from `from` to `to` inclusive, the newest first
+    pub fn books_added_between_page(
+        &self,
+        from: Date,
+        to: Date,
+        page: PageRequest,
+    ) -> Result<Page<Book>> {
+        debug!("books_added_between_page <- {from}, {to}, {page:?}");
+
+        self.books_page::<queries::BooksAddedBetween>((from, to), page)
+    }
+
+    /// Returns the range of the last `days` days of the library
+    ///
+    /// Days are counted back from the latest date in the database rather than from today,
+    /// so the library which was not updated for a while still has its latest arrivals
+    pub fn last_days(&self, days: u32) -> Result<Option<(Date, Date)>> {
+        debug!("last_days <- {days}");
+
+        match self.latest_date()? {
+            Some(latest) if days > 0 => {
+                let to = latest.parse::<Date>()?;
+                Ok(Some((to.days_before(days - 1), to)))
+            }
+            _ => Ok(None),
+        }
+    }
+
+    /// Returns books added during the last `days` days of the library, the newest first
+    pub fn books_added_in_last_days(&self, days: u32) -> Result<Vec<Book>> {
+        match self.last_days(days)? {
+            Some((from, to)) => self.books_added_between(from, to),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    /// Returns the page of books added during the last `days` days of the library
+    pub fn books_added_in_last_days_page(
+        &self,
+        days: u32,
+        page: PageRequest,
+    ) -> Result<Page<Book>> {
+        match self.last_days(days)? {
+            Some((from, to)) => self.books_added_between_page(from, to, page),
+            None => Ok(Page::new(Vec::new(), 0, page)),
         }
     }
 
     /// Returns Series by exact serie name
-    pub fn books_by_book_title(&self, name: &String) -> anyhow::Result<Vec<Book>> {
+    pub fn books_by_book_title(&self, name: &String) -> Result<Vec<Book>> {
         debug!("series_by_serie_name <- {name}");
 
-        let query = Query::BooksByBookTitle;
-        if let Mapper::Book(mapper) = Query::mapper(&query) {
-            let mut statement = self.prepare(&query)?;
-            let rows = statement.query([name])?.mapped(mapper);
-            let res = transfrom(rows)?;
-            Ok(res)
-        } else {
-            Err(anyhow::anyhow!("Unexpected mapper"))
-        }
+        self.books::<queries::BooksByBookTitle>((name.clone(),))
+    }
+
+    /// Returns the page of books by exact title
+    pub fn books_by_book_title_page(
+        &self,
+        name: &String,
+        page: PageRequest,
+    ) -> Result<Page<Book>> {
+        debug!("books_by_book_title_page <- {name}, {page:?}");
+
+        self.books_page::<queries::BooksByBookTitle>((name.clone(),), page)
     }
 
     /// Returns Metas of Genres
-    pub fn meta_genres(&self) -> anyhow::Result<Vec<String>> {
+    pub fn meta_genres(&self) -> Result<Vec<String>> {
         debug!("meta_genres <- ");
 
-        let query = Query::MetaGenres;
-        if let Mapper::String(mapper) = Query::mapper(&query) {
-            let mut statement = self.prepare(&query)?;
-            let rows = statement.query([])?.mapped(mapper);
-            let res = transfrom(rows)?;
-            Ok(res)
-        } else {
-            Err(anyhow::anyhow!("Unexpected mapper"))
-        }
+        self.fetch::<queries::MetaGenres>(())
     }
 
     /// Returns genres Meta
-    pub fn genres_by_meta(&self, meta: &String) -> anyhow::Result<Vec<Value>> {
+    pub fn genres_by_meta(&self, meta: &String) -> Result<Vec<Value>> {
         debug!("genres_by_meta <- {meta}");
 
-        let query = Query::GenresByMeta;
-        if let Mapper::Value(mapper) = Query::mapper(&query) {
-            let mut statement = self.prepare(&query)?;
-            let rows = statement.query([meta])?.mapped(mapper);
-            let res = transfrom(rows)?;
-            Ok(res)
-        } else {
-            Err(anyhow::anyhow!("Unexpected mapper"))
+        self.fetch::<queries::GenresByMeta>((meta.clone(),))
+    }
+
+    /// Returns Genre by id
+    pub fn genre_by_id(&self, gid: u32) -> Result<Option<Value>> {
+        debug!("genre_by_id <- {gid}");
+
+        let res = self.fetch::<queries::GenreById>((gid,))?;
+        Ok(res.into_iter().next())
+    }
+
+    /// Returns genres of the book
+    pub fn genres_by_book_id(&self, id: u32) -> Result<Vec<Genre>> {
+        debug!("genres_by_book_id <- {id}");
+
+        let mut genres = self.genres_by_books_ids(vec![id])?;
+        Ok(genres.remove(&id).unwrap_or_default())
+    }
+
+    /// Returns genres of the books by book id
+    pub fn genres_by_books_ids(&self, ids: Vec<u32>) -> Result<HashMap<u32, Vec<Genre>>> {
+        debug!("genres_by_books_ids <- {:?}", ids);
+
+        let rows = self.fetch::<queries::GenresByBooksIds>((Array(ids),))?;
+        let mut res: HashMap<u32, Vec<Genre>> = HashMap::new();
+        for (book_id, genre) in rows {
+            res.entry(book_id).or_default().push(genre);
         }
+        Ok(res)
+    }
+
+    /// Returns the date of the most recently added book
+    pub fn latest_date(&self) -> Result<Option<String>> {
+        debug!("latest_date <- ");
+
+        let res = self.fetch::<queries::LatestDate>(())?;
+        Ok(res.into_iter().next())
     }
 }
 
 impl TryFrom<&str> for OpdsApi {
-    type Error = anyhow::Error;
+    type Error = Error;
 
-    fn try_from(database: &str) -> anyhow::Result<Self> {
+    fn try_from(database: &str) -> Result<Self> {
         debug!("database: {database}");
 
         let conn = Connection::open(database).inspect_err(|e| error!("{e}"))?;
-        conn.create_collation("opds", collation::collation)?;
-
-        let flags = FunctionFlags::SQLITE_DETERMINISTIC;
-        conn.create_scalar_function("LOWER", 1, flags, |ctx| {
-            ctx.get::<String>(0).and_then(|s| Ok(s.to_lowercase()))
-        })?;
-        rusqlite::vtab::array::load_module(&conn)?;
-        Ok(Self::new(conn))
+        let api = OpdsApi::with_functions(conn)?;
+        api.check_schema()?;
+        #[cfg(feature = "validate")]
+        if !api.is_empty()? {
+            api.validate()?;
+        }
+        Ok(api)
     }
 }
 impl TryFrom<&String> for OpdsApi {
-    type Error = anyhow::Error;
+    type Error = Error;
 
-    fn try_from(database: &String) -> anyhow::Result<Self> {
+    fn try_from(database: &String) -> Result<Self> {
         OpdsApi::try_from(database.as_str())
     }
 }
 
-fn transfrom<T, E, I>(collection: I) -> anyhow::Result<Vec<T>, E>
+/// Converts free text into FTS5 query where every word is a quoted prefix
+fn fts_pattern(text: &str) -> String {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(|word| format!("\"{word}\"*"))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn transfrom<T, E, I>(collection: I) -> std::result::Result<Vec<T>, E>
 where
     I: IntoIterator<Item = rusqlite::Result<T, E>>,
 {
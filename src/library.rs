@@ -0,0 +1,330 @@
+use log::debug;
+use zip::{result::ZipError, write::SimpleFileOptions, CompressionMethod, ZipArchive, ZipWriter};
+
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::{self, BufReader, Cursor, Read, Seek, Write},
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex, PoisonError},
+};
+
+use crate::{
+    fb2::{Cover, Description},
+    importer::Import,
+    Error, Result,
+};
+
+/// Most books remembered by [Library::details], the cache is cleared when it is full
+const DETAILS_CACHE_SIZE: usize = 10_000;
+
+/// Annotation and cover mime type of the book, without the cover itself
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Details {
+    pub annotation: Option<String>,
+    pub cover: Option<String>,
+}
+
+/// Directory with the library archives `fb2-<first>-<last>.zip`
+///
+/// The clones share the details of the books already parsed.
+#[derive(Debug, Clone)]
+pub struct Library {
+    root: PathBuf,
+    /// Ranges of the book ids with their archives
+    archives: Vec<(u32, u32, PathBuf)>,
+    details: Arc<Mutex<HashMap<u32, Details>>>,
+}
+impl Library {
+    /// Indexes the archives of the directory, the archives added later are not served
+    pub fn new<P: Into<PathBuf>>(root: P) -> Result<Self> {
+        let root = root.into();
+        debug!("new <- {}", root.display());
+
+        let mut archives = Vec::new();
+        for entry in std::fs::read_dir(&root)? {
+            let path = entry?.path();
+            let name = path
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_default();
+            if !name.ends_with(".zip") {
+                continue;
+            }
+            if let Some((first, last)) = Import::range(&name) {
+                archives.push((first, last, path));
+            }
+        }
+        archives.sort();
+        Ok(Self {
+            root,
+            archives,
+            details: Arc::default(),
+        })
+    }
+
+    /// Returns file name of the book in the archive
+    pub fn file_name(id: u32) -> String {
+        format!("{id}.fb2")
+    }
+
+    /// Returns path of the archive which contains the book
+    pub fn archive(&self, id: u32) -> Result<&Path> {
+        debug!("archive <- {id}");
+
+        self.archives
+            .iter()
+            .find(|(first, last, _)| *first <= id && id <= *last)
+            .map(|(_, _, path)| path.as_path())
+            .ok_or_else(|| {
+                Error::NotFound(format!(
+                    "Archive of the book {id} in {}",
+                    self.root.display()
+                ))
+            })
+    }
+
+    /// Opens `<id>.fb2` in the archive and passes it with its unpacked size to the reader
+    fn open<T, F>(&self, id: u32, read: F) -> Result<T>
+    where
+        F: FnOnce(&mut dyn Read, u64) -> Result<T>,
+    {
+        let path = self.archive(id)?;
+        let file = File::open(path).map_err(|e| match e.kind() {
+            io::ErrorKind::NotFound => Error::NotFound(format!("Archive {}", path.display())),
+            _ => Error::Io(e),
+        })?;
+        let mut archive = ZipArchive::new(file)?;
+        let name = Self::file_name(id);
+        let mut file = match archive.by_name(&name) {
+            Ok(file) => file,
+            Err(ZipError::FileNotFound) => {
+                return Err(Error::NotFound(format!("{name} in {}", path.display())))
+            }
+            Err(e) => return Err(e.into()),
+        };
+        let size = file.size();
+        read(&mut file, size)
+    }
+
+    /// Returns unpacked size of the `<id>.fb2`
+    pub fn book_size(&self, id: u32) -> Result<u64> {
+        debug!("book_size <- {id}");
+        self.open(id, |_, size| Ok(size))
+    }
+
+    /// Streams `<id>.fb2` from the archive to the writer, returns number of written bytes
+    pub fn copy_book<W: Write>(&self, id: u32, out: &mut W) -> Result<u64> {
+        debug!("copy_book <- {id}");
+        self.open(id, |file, _| Ok(io::copy(file, out)?))
+    }
+
+    /// Returns content of the `<id>.fb2`
+    pub fn book(&self, id: u32) -> Result<Vec<u8>> {
+        let mut out = Vec::new();
+        self.copy_book(id, &mut out)?;
+        Ok(out)
+    }
+
+    /// Writes `<id>.fb2` re-zipped as the single file archive
+    pub fn copy_zipped_book<W: Write + Seek>(&self, id: u32, out: W) -> Result<()> {
+        debug!("copy_zipped_book <- {id}");
+
+        let mut zip = ZipWriter::new(out);
+        let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+        zip.start_file(Self::file_name(id), options)?;
+        self.copy_book(id, &mut zip)?;
+        zip.finish()?;
+        Ok(())
+    }
+
+    /// Returns content of the `<id>.fb2.zip`
+    pub fn zipped_book(&self, id: u32) -> Result<Vec<u8>> {
+        let mut out = Cursor::new(Vec::new());
+        self.copy_zipped_book(id, &mut out)?;
+        Ok(out.into_inner())
+    }
+
+    /// Returns annotation and cover of the book, remembers its [Details]
+    ///
+    /// The book missing in the archive is remembered without the details.
+    pub fn description(&self, id: u32) -> Result<Description> {
+        debug!("description <- {id}");
+
+        let result = self.open(id, |file, _| Description::read(BufReader::new(file)));
+        let details = match &result {
+            Ok(description) => Details {
+                annotation: description.annotation.clone(),
+                cover: description.cover.as_ref().map(|cover| cover.mime.clone()),
+            },
+            Err(Error::NotFound(_)) => Details::default(),
+            Err(_) => return result,
+        };
+        let mut cache = self.details.lock().unwrap_or_else(PoisonError::into_inner);
+        if cache.len() >= DETAILS_CACHE_SIZE {
+            cache.clear();
+        }
+        cache.insert(id, details);
+        result
+    }
+
+    /// Returns the details of the book if it was already parsed, never reads the archive
+    pub fn details(&self, id: u32) -> Option<Details> {
+        let cache = self.details.lock().unwrap_or_else(PoisonError::into_inner);
+        cache.get(&id).cloned()
+    }
+
+    /// Returns annotation of the book
+    pub fn book_annotation(&self, id: u32) -> Result<Option<String>> {
+        Ok(self.description(id)?.annotation)
+    }
+
+    /// Returns mime type and content of the book cover
+    pub fn book_cover(&self, id: u32) -> Result<Option<(String, Vec<u8>)>> {
+        let cover = self.description(id)?.cover;
+        Ok(cover.map(|Cover { mime, bytes }| (mime, bytes)))
+    }
+
+    /// Returns the book cover scaled to fit `size` x `size` as JPEG
+    #[cfg(feature = "thumbnail")]
+    pub fn book_thumbnail(&self, id: u32, size: u32) -> Result<Option<(String, Vec<u8>)>> {
+        debug!("book_thumbnail <- {id}, {size}");
+
+        match self.description(id)?.cover {
+            Some(cover) => {
+                let image = image::load_from_memory(&cover.bytes)?.thumbnail(size, size);
+                let mut out = Cursor::new(Vec::new());
+                image
+                    .to_rgb8()
+                    .write_to(&mut out, image::ImageFormat::Jpeg)?;
+                Ok(Some((String::from("image/jpeg"), out.into_inner())))
+            }
+            None => Ok(None),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(feature = "thumbnail")]
+    use base64::{engine::general_purpose::STANDARD, Engine};
+
+    const FB2: &str = r##"<FictionBook xmlns:l="http://www.w3.org/1999/xlink">
+        <description><title-info>
+        <annotation><p>Annotation</p></annotation>
+        <coverpage><image l:href="#cover.jpg"/></coverpage>
+        </title-info></description>
+        <binary id="cover.jpg" content-type="image/jpeg">/9j/4AA=</binary>
+        </FictionBook>"##;
+
+    fn library() -> Result<(tempfile::TempDir, Library)> {
+        let dir = tempfile::tempdir()?;
+        for (name, files) in [
+            ("fb2-100-199.zip", vec![(150, "first"), (199, FB2)]),
+            ("fb2-200-299.zip", vec![(200, "second"), (299, "third")]),
+        ] {
+            let mut zip = ZipWriter::new(File::create(dir.path().join(name))?);
+            for (id, content) in files {
+                zip.start_file(Library::file_name(id), SimpleFileOptions::default())?;
+                zip.write_all(content.as_bytes())?;
+            }
+            zip.finish()?;
+        }
+        std::fs::write(dir.path().join("fb2-300-399.inp"), "")?;
+        let library = Library::new(dir.path())?;
+        Ok((dir, library))
+    }
+
+    #[test]
+    fn archive() -> Result<()> {
+        let (dir, library) = library()?;
+
+        assert_eq!(library.archive(150)?, dir.path().join("fb2-100-199.zip"));
+        assert_eq!(library.archive(299)?, dir.path().join("fb2-200-299.zip"));
+        assert!(library.archive(350).is_err());
+
+        // the archives are indexed once
+        std::fs::remove_file(dir.path().join("fb2-200-299.zip"))?;
+        assert_eq!(library.archive(200)?, dir.path().join("fb2-200-299.zip"));
+        assert!(Library::new(dir.path())?.archive(200).is_err());
+        assert!(Library::new(dir.path().join("missing")).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn book() -> Result<()> {
+        let (_dir, library) = library()?;
+
+        assert_eq!(library.book(150)?, b"first");
+        assert_eq!(library.book(299)?, b"third");
+        assert_eq!(library.book_size(299)?, 5);
+
+        assert!(matches!(library.book(250), Err(Error::NotFound(_))));
+        assert!(matches!(library.book(350), Err(Error::NotFound(_))));
+        Ok(())
+    }
+
+    #[test]
+    fn zipped_book() -> Result<()> {
+        let (_dir, library) = library()?;
+
+        let zipped = library.zipped_book(200)?;
+        let mut archive = ZipArchive::new(Cursor::new(zipped))?;
+        assert_eq!(archive.len(), 1);
+
+        let mut content = String::new();
+        archive.by_name("200.fb2")?.read_to_string(&mut content)?;
+        assert_eq!(content, "second");
+        Ok(())
+    }
+
+    #[test]
+    fn description() -> Result<()> {
+        let (_dir, library) = library()?;
+
+        assert_eq!(library.book_annotation(199)?.as_deref(), Some("Annotation"));
+        assert_eq!(
+            library.book_cover(199)?,
+            Some((
+                String::from("image/jpeg"),
+                vec![0xFF, 0xD8, 0xFF, 0xE0, 0x00]
+            ))
+        );
+        assert_eq!(library.book_annotation(150)?, None);
+        assert_eq!(library.book_cover(150)?, None);
+
+        let details = Details {
+            annotation: Some(String::from("Annotation")),
+            cover: Some(String::from("image/jpeg")),
+        };
+        assert_eq!(library.clone().details(199), Some(details));
+        assert_eq!(library.details(150), Some(Details::default()));
+        assert!(library.description(250).is_err());
+        assert_eq!(library.details(250), Some(Details::default()));
+        assert_eq!(library.details(299), None);
+        Ok(())
+    }
+
+    #[cfg(feature = "thumbnail")]
+    #[test]
+    fn book_thumbnail() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let mut png = Cursor::new(Vec::new());
+        image::RgbImage::new(400, 200).write_to(&mut png, image::ImageFormat::Png)?;
+        let fb2 = FB2.replace("/9j/4AA=", &STANDARD.encode(png.into_inner()));
+
+        let mut zip = ZipWriter::new(File::create(dir.path().join("fb2-1-1.zip"))?);
+        zip.start_file(Library::file_name(1), SimpleFileOptions::default())?;
+        zip.write_all(fb2.as_bytes())?;
+        zip.finish()?;
+
+        let (mime, bytes) = Library::new(dir.path())?
+            .book_thumbnail(1, 100)?
+            .expect("thumbnail");
+        assert_eq!(mime, "image/jpeg");
+        let thumbnail = image::load_from_memory(&bytes)?;
+        assert_eq!((thumbnail.width(), thumbnail.height()), (100, 50));
+        Ok(())
+    }
+}
@@ -0,0 +1,1113 @@
+use log::debug;
+
+use std::{collections::HashMap, fmt};
+
+use crate::{
+    book::format_size,
+    library::{Details, Library},
+    Author, AuthorInfo, Book, Date, Error, Found, Genre, OpdsApi, Page, PageRequest, Result,
+};
+
+/// Mime type of the navigation feed
+pub const NAVIGATION: &str = "application/atom+xml;profile=opds-catalog;kind=navigation";
+/// Mime type of the acquisition feed
+pub const ACQUISITION: &str = "application/atom+xml;profile=opds-catalog;kind=acquisition";
+/// Mime type of the FB2 book
+pub const FB2: &str = "application/x-fictionbook+xml";
+/// Mime type of the zipped FB2 book
+pub const FB2_ZIP: &str = "application/fb2+zip";
+
+const REL_ACQUISITION: &str = "http://opds-spec.org/acquisition/open-access";
+const REL_ACQUISITION_PREFIX: &str = "http://opds-spec.org/acquisition";
+/// Mime type of the OpenSearch description document
+pub const OPENSEARCH: &str = "application/opensearchdescription+xml";
+/// Relation of the book cover link
+pub const REL_IMAGE: &str = "http://opds-spec.org/image";
+/// Relation of the book cover thumbnail link
+pub const REL_THUMBNAIL: &str = "http://opds-spec.org/image/thumbnail";
+/// Number of the latest days shown in the New arrivals feed
+pub const NEW_ARRIVALS_DAYS: u32 = 7;
+const JPEG: &str = "image/jpeg";
+const URN: &str = "urn:opds_api";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Kind {
+    Navigation,
+    Acquisition,
+}
+impl Kind {
+    pub fn mime(&self) -> &'static str {
+        match self {
+            Self::Navigation => NAVIGATION,
+            Self::Acquisition => ACQUISITION,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Link {
+    pub rel: String,
+    pub href: String,
+    pub mime: String,
+    pub title: Option<String>,
+}
+impl Link {
+    pub fn new<R: Into<String>, H: Into<String>, M: Into<String>>(
+        rel: R,
+        href: H,
+        mime: M,
+    ) -> Self {
+        Self {
+            rel: rel.into(),
+            href: href.into(),
+            mime: mime.into(),
+            title: None,
+        }
+    }
+
+    pub fn with_title<T: Into<String>>(mut self, title: T) -> Self {
+        self.title = Some(title.into());
+        self
+    }
+}
+impl fmt::Display for Link {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            fmt,
+            r#"<link rel="{}" href="{}" type="{}""#,
+            escape(&self.rel),
+            escape(&self.href),
+            escape(&self.mime)
+        )?;
+        if let Some(title) = &self.title {
+            write!(fmt, r#" title="{}""#, escape(title))?;
+        }
+        fmt.write_str("/>")
+    }
+}
+
+/// Author of the entry
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Person {
+    pub name: String,
+    pub uri: Option<String>,
+}
+impl fmt::Display for Person {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(fmt, "<author><name>{}</name>", escape(&self.name))?;
+        if let Some(uri) = &self.uri {
+            write!(fmt, "<uri>{}</uri>", escape(uri))?;
+        }
+        fmt.write_str("</author>")
+    }
+}
+
+/// Genre of the entry
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Category {
+    pub term: String,
+    pub label: String,
+    /// Feed of the genre
+    pub href: String,
+}
+impl fmt::Display for Category {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            fmt,
+            r#"<category term="{}" label="{}"/>"#,
+            escape(&self.term),
+            escape(&self.label)
+        )
+    }
+}
+
+/// Serie the entry belongs to
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Member {
+    pub name: String,
+    pub position: Option<u32>,
+    pub href: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Entry {
+    pub id: String,
+    pub title: String,
+    pub updated: String,
+    pub authors: Vec<Person>,
+    pub serie: Option<Member>,
+    /// Language code of the publication
+    pub language: Option<String>,
+    pub categories: Vec<Category>,
+    /// Annotation of the publication
+    pub summary: Option<String>,
+    pub content: Option<String>,
+    pub links: Vec<Link>,
+}
+impl Entry {
+    /// Returns true if the entry is a publication, i.e. has an acquisition link
+    pub fn is_publication(&self) -> bool {
+        self.links
+            .iter()
+            .any(|link| link.rel.starts_with(REL_ACQUISITION_PREFIX))
+    }
+}
+impl fmt::Display for Entry {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.write_str("<entry>")?;
+        write!(fmt, "<id>{}</id>", escape(&self.id))?;
+        write!(fmt, "<title>{}</title>", escape(&self.title))?;
+        write!(fmt, "<updated>{}</updated>", escape(&self.updated))?;
+        for author in &self.authors {
+            write!(fmt, "{author}")?;
+        }
+        if let Some(language) = &self.language {
+            write!(fmt, "<dc:language>{}</dc:language>", escape(language))?;
+        }
+        for category in &self.categories {
+            write!(fmt, "{category}")?;
+        }
+        if let Some(summary) = &self.summary {
+            write!(fmt, r#"<summary type="text">{}</summary>"#, escape(summary))?;
+        }
+        if let Some(content) = &self.content {
+            write!(fmt, r#"<content type="text">{}</content>"#, escape(content))?;
+        }
+        for link in &self.links {
+            write!(fmt, "{link}")?;
+        }
+        if let Some(serie) = &self.serie {
+            let link = Link::new("related", serie.href.as_str(), ACQUISITION)
+                .with_title(serie.name.as_str());
+            write!(fmt, "{link}")?;
+        }
+        fmt.write_str("</entry>")
+    }
+}
+
+/// Titled group of entries, e.g. books of one serie
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Group {
+    pub title: String,
+    pub href: Option<String>,
+    pub entries: Vec<Entry>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Feed {
+    pub id: String,
+    pub title: String,
+    pub updated: String,
+    pub kind: Kind,
+    pub links: Vec<Link>,
+    pub entries: Vec<Entry>,
+    pub groups: Vec<Group>,
+}
+impl Feed {
+    /// Appends the entry to the last group if it has the same title, otherwise starts the new group
+    fn push_grouped(&mut self, title: String, href: Option<String>, entry: Entry) {
+        match self.groups.last_mut() {
+            Some(group) if group.title == title && group.href == href => group.entries.push(entry),
+            _ => self.groups.push(Group {
+                title,
+                href,
+                entries: vec![entry],
+            }),
+        }
+    }
+}
+impl fmt::Display for Feed {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.write_str(r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+        fmt.write_str(r#"<feed xmlns="http://www.w3.org/2005/Atom" xmlns:dc="http://purl.org/dc/terms/" xmlns:opds="http://opds-spec.org/2010/catalog">"#)?;
+        write!(fmt, "<id>{}</id>", escape(&self.id))?;
+        write!(fmt, "<title>{}</title>", escape(&self.title))?;
+        write!(fmt, "<updated>{}</updated>", escape(&self.updated))?;
+        fmt.write_str("<author><name>opds_api</name></author>")?;
+        for link in &self.links {
+            write!(fmt, "{link}")?;
+        }
+        for entry in &self.entries {
+            write!(fmt, "{entry}")?;
+        }
+        for group in &self.groups {
+            for entry in &group.entries {
+                if let Some(href) = &group.href {
+                    let mut entry = entry.clone();
+                    let link = Link::new("collection", href.as_str(), self.kind.mime())
+                        .with_title(group.title.as_str());
+                    entry.links.push(link);
+                    write!(fmt, "{entry}")?;
+                } else {
+                    write!(fmt, "{entry}")?;
+                }
+            }
+        }
+        fmt.write_str("</feed>")
+    }
+}
+
+/// Builds OPDS feeds from the OpdsApi queries
+///
+/// All links are built as `{base}/{path}`, e.g. `/opds/author/43/2/184`
+/// and the long lists are split into pages linked by `?page=N`
+pub struct Catalog<'a> {
+    api: &'a OpdsApi,
+    base: String,
+    page: PageRequest,
+    library: Option<&'a Library>,
+}
+impl<'a> Catalog<'a> {
+    pub fn new<S: Into<String>>(api: &'a OpdsApi, base: S) -> Self {
+        Self {
+            api,
+            base: base.into().trim_end_matches('/').to_owned(),
+            page: PageRequest::default(),
+            library: None,
+        }
+    }
+
+    /// Adds the annotations and the cover links of the books found in the library
+    pub fn with_library(mut self, library: &'a Library) -> Self {
+        self.library = Some(library);
+        self
+    }
+
+    /// Selects the zero based page of the paginated feeds
+    pub fn with_page(mut self, number: u32) -> Self {
+        self.page = PageRequest::number(number, self.page.limit);
+        self
+    }
+
+    fn url(&self, path: &str) -> String {
+        format!("{}{path}", self.base)
+    }
+
+    fn feed<I, T>(&self, kind: Kind, id: I, title: T, path: &str) -> Result<Feed>
+    where
+        I: AsRef<str>,
+        T: Into<String>,
+    {
+        let updated = self.api.latest_date()?.unwrap_or_default();
+        Ok(Feed {
+            id: format!("{URN}:{}", id.as_ref()),
+            title: title.into(),
+            updated: timestamp(&updated),
+            kind,
+            links: vec![
+                Link::new("self", self.url(path), kind.mime()),
+                Link::new("start", self.url(""), NAVIGATION),
+                Link::new("search", self.url("/opensearch.xml"), OPENSEARCH),
+            ],
+            entries: Vec::new(),
+            groups: Vec::new(),
+        })
+    }
+
+    /// Adds `first`, `previous`, `next` and `last` links of the paginated feed
+    fn paginate<T>(&self, feed: &mut Feed, path: &str, page: &Page<T>) {
+        if page.prev.is_none() && page.next.is_none() {
+            return;
+        }
+        let href = |request: PageRequest| format!("{}?page={}", self.url(path), request.index());
+        let last = page.total.saturating_sub(1) / self.page.limit.max(1);
+        let mut links = vec![("first", Some(PageRequest::number(0, self.page.limit)))];
+        links.push(("previous", page.prev));
+        links.push(("next", page.next));
+        links.push(("last", Some(PageRequest::number(last, self.page.limit))));
+        for (rel, request) in links {
+            if let Some(request) = request {
+                feed.links
+                    .push(Link::new(rel, href(request), feed.kind.mime()));
+            }
+        }
+    }
+
+    fn navigation<I, T, C>(
+        &self,
+        feed: &Feed,
+        id: I,
+        title: T,
+        content: C,
+        path: &str,
+        kind: Kind,
+    ) -> Entry
+    where
+        I: AsRef<str>,
+        T: Into<String>,
+        C: Into<String>,
+    {
+        Entry {
+            id: format!("{URN}:{}", id.as_ref()),
+            title: title.into(),
+            updated: feed.updated.clone(),
+            authors: Vec::new(),
+            serie: None,
+            language: None,
+            categories: Vec::new(),
+            summary: None,
+            content: Some(content.into()),
+            links: vec![Link::new("subsection", self.url(path), kind.mime())],
+        }
+    }
+
+    fn person(&self, author: &Author) -> Person {
+        let path = format!(
+            "/author/{}/{}/{}",
+            author.first_name.id, author.middle_name.id, author.last_name.id
+        );
+        Person {
+            name: author.to_string(),
+            uri: Some(self.url(&path)),
+        }
+    }
+
+    /// Navigation entry of the author books with the books and series counts
+    fn author_entry(&self, feed: &Feed, info: &AuthorInfo) -> Entry {
+        let author = &info.author;
+        let (fid, mid, lid) = (
+            author.first_name.id,
+            author.middle_name.id,
+            author.last_name.id,
+        );
+        let id = format!("author:{fid}:{mid}:{lid}");
+        let path = format!("/author/{fid}/{mid}/{lid}");
+        self.navigation(
+            feed,
+            id,
+            author.to_string(),
+            info.summary(),
+            &path,
+            Kind::Acquisition,
+        )
+    }
+
+    /// Returns names of the books series
+    fn serie_names(&self, books: &[Book]) -> Result<HashMap<u32, String>> {
+        let mut ids = books.iter().filter_map(|book| book.sid).collect::<Vec<_>>();
+        ids.sort();
+        ids.dedup();
+        if ids.is_empty() {
+            return Ok(HashMap::new());
+        }
+        let names = self
+            .api
+            .series_by_ids(ids)?
+            .into_iter()
+            .map(|serie| (serie.id, serie.name))
+            .collect();
+        Ok(names)
+    }
+
+    /// Returns genres of the books
+    fn book_genres(&self, books: &[Book]) -> Result<HashMap<u32, Vec<Genre>>> {
+        if books.is_empty() {
+            return Ok(HashMap::new());
+        }
+        let ids = books.iter().map(|book| book.id).collect();
+        self.api.genres_by_books_ids(ids)
+    }
+
+    fn book(
+        &self,
+        book: &Book,
+        series: &HashMap<u32, String>,
+        genres: &HashMap<u32, Vec<Genre>>,
+    ) -> Entry {
+        let mut links = vec![
+            Link::new(
+                REL_ACQUISITION,
+                self.url(&format!("/book/{}/fb2", book.id)),
+                FB2,
+            ),
+            Link::new(
+                REL_ACQUISITION,
+                self.url(&format!("/book/{}/fb2.zip", book.id)),
+                FB2_ZIP,
+            ),
+        ];
+        // The links are built from the id only, the cover route reads the archive. Until then
+        // the cover is assumed to be JPEG, the most common one.
+        let details = self.library.map(|library| {
+            library.details(book.id).unwrap_or_else(|| Details {
+                annotation: None,
+                cover: Some(String::from(JPEG)),
+            })
+        });
+        let (summary, cover) = details
+            .map(|details| (details.annotation, details.cover))
+            .unwrap_or_default();
+        if let Some(mime) = cover {
+            let thumbnail = if cfg!(feature = "thumbnail") {
+                JPEG
+            } else {
+                mime.as_str()
+            };
+            links.push(Link::new(
+                REL_IMAGE,
+                self.url(&format!("/book/{}/cover", book.id)),
+                mime.as_str(),
+            ));
+            links.push(Link::new(
+                REL_THUMBNAIL,
+                self.url(&format!("/book/{}/thumbnail", book.id)),
+                thumbnail,
+            ));
+        }
+        let serie = book.sid.and_then(|sid| {
+            series.get(&sid).map(|name| Member {
+                name: name.clone(),
+                position: book.idx.filter(|idx| *idx > 0),
+                href: self.url(&format!("/serie/{sid}")),
+            })
+        });
+        let content = match book.idx {
+            Some(idx) if idx > 0 => format!("#{idx}, {}", format_size(book.size)),
+            _ => format_size(book.size),
+        };
+
+        Entry {
+            id: format!("{URN}:book:{}", book.id),
+            title: book.name.clone(),
+            updated: timestamp(&book.added),
+            authors: book
+                .authors
+                .iter()
+                .map(|author| self.person(author))
+                .collect(),
+            serie,
+            language: Some(book.lang.clone()).filter(|lang| !lang.is_empty()),
+            categories: genres
+                .get(&book.id)
+                .into_iter()
+                .flatten()
+                .map(|genre| Category {
+                    term: genre.code.clone(),
+                    label: genre.name.clone(),
+                    href: self.url(&format!("/genre/{}", genre.id)),
+                })
+                .collect(),
+            summary,
+            content: Some(content),
+            links,
+        }
+    }
+
+    fn acquisition<I, T>(
+        &self,
+        id: I,
+        title: T,
+        path: &str,
+        books: Page<Book>,
+    ) -> Result<Feed>
+    where
+        I: AsRef<str>,
+        T: Into<String>,
+    {
+        let mut feed = self.feed(Kind::Acquisition, id, title, path)?;
+        self.paginate(&mut feed, path, &books);
+        let series = self.serie_names(&books.items)?;
+        let genres = self.book_genres(&books.items)?;
+        feed.entries = books
+            .items
+            .iter()
+            .map(|book| self.book(book, &series, &genres))
+            .collect();
+        Ok(feed)
+    }
+
+    /// Adds entries of the complete names (`exact` path) and of the longer prefixes (`nvc` path)
+    fn prefixes(
+        &self,
+        feed: &mut Feed,
+        nvc: &str,
+        (exact, kind): (&str, Kind),
+        (complete, incomplete): (Vec<String>, Vec<String>),
+    ) {
+        for value in complete {
+            let path = format!("/{exact}/{}", encode(&value));
+            let id = format!("{exact}:{}", encode(&value));
+            let entry = self.navigation(feed, id, value.clone(), value, &path, kind);
+            feed.entries.push(entry);
+        }
+        for value in incomplete {
+            let path = format!("/{nvc}/{}", encode(&value));
+            let id = format!("{nvc}:{}", encode(&value));
+            let title = format!("{value}…");
+            let entry = self.navigation(feed, id, title, value, &path, Kind::Navigation);
+            feed.entries.push(entry);
+        }
+    }
+
+    /// OpenSearch description document of the search feed
+    ///
+    /// The template points to `{base}/search/{searchTerms}` which returns
+    /// the feed of the `mime` type
+    pub fn opensearch(&self, mime: &str) -> String {
+        let template = format!("{}/{{searchTerms}}", self.url("/search"));
+        format!(
+            concat!(
+                r#"<?xml version="1.0" encoding="UTF-8"?>"#,
+                r#"<OpenSearchDescription xmlns="http://a9.com/-/spec/opensearch/1.1/">"#,
+                "<ShortName>opds_api</ShortName>",
+                "<Description>Search authors, series and titles</Description>",
+                "<InputEncoding>UTF-8</InputEncoding>",
+                "<OutputEncoding>UTF-8</OutputEncoding>",
+                r#"<Url type="{}" template="{}"/>"#,
+                "</OpenSearchDescription>"
+            ),
+            escape(mime),
+            escape(&template)
+        )
+    }
+
+    /// Acquisition feed of the full-text search ranked by the match
+    ///
+    /// The first page starts with the branches of the term into authors, series and titles,
+    /// branches without matches are omitted. Found authors and series link to their feeds,
+    /// the title counts all hits.
+    pub fn search(&self, term: &String) -> Result<Feed> {
+        debug!("search <- {term}");
+
+        let found = self.api.search_page(term, self.page)?;
+        let path = format!("/search/{}", encode(term));
+        let id = format!("search:{}", encode(term));
+        let title = format!("Search: {term} [{}]", found.total);
+        let mut feed = self.feed(Kind::Acquisition, id, title, &path)?;
+        self.paginate(&mut feed, &path, &found);
+
+        if found.prev.is_none() {
+            let branches = [
+                ("authors", "Authors", self.api.search_authors_by_prefix(term)?),
+                ("series", "Series", self.api.search_series_by_prefix(term)?),
+                ("titles", "Titles", self.api.search_books_by_prefix(term)?),
+            ];
+            for (nvc, title, (complete, incomplete)) in branches {
+                let count = complete.len() + incomplete.len();
+                if count > 0 {
+                    let entry = self.navigation(
+                        &feed,
+                        format!("search:{nvc}:{}", encode(term)),
+                        title,
+                        format!("{count} matches"),
+                        &format!("/{nvc}/{}", encode(term)),
+                        Kind::Navigation,
+                    );
+                    feed.entries.push(entry);
+                }
+            }
+        }
+
+        let found = found.items;
+        let books = found
+            .iter()
+            .filter_map(|found| match found {
+                Found::Book(book) => Some(book.clone()),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        let series = self.serie_names(&books)?;
+        let genres = self.book_genres(&books)?;
+        for found in &found {
+            let entry = match found {
+                Found::Author(author) => {
+                    let (fid, mid, lid) = (
+                        author.first_name.id,
+                        author.middle_name.id,
+                        author.last_name.id,
+                    );
+                    self.navigation(
+                        &feed,
+                        format!("author:{fid}:{mid}:{lid}"),
+                        author.to_string(),
+                        "Author",
+                        &format!("/author/{fid}/{mid}/{lid}"),
+                        Kind::Acquisition,
+                    )
+                }
+                Found::Serie(serie) => self.navigation(
+                    &feed,
+                    format!("serie:{}", serie.id),
+                    format!("{} [{}]", serie.name, serie.author),
+                    format!("{} books", serie.count),
+                    &format!("/serie/{}", serie.id),
+                    Kind::Acquisition,
+                ),
+                Found::Book(book) => self.book(book, &series, &genres),
+            };
+            feed.entries.push(entry);
+        }
+        Ok(feed)
+    }
+
+    /// Root navigation feed
+    pub fn root(&self) -> Result<Feed> {
+        debug!("root <- ");
+
+        let mut feed = self.feed(Kind::Navigation, "root", "Catalog", "")?;
+        let entries = [
+            ("authors", "Authors", "Authors by last name", "/authors/"),
+            ("series", "Series", "Series by name", "/series/"),
+            ("titles", "Titles", "Books by title", "/titles/"),
+            ("genres", "Genres", "Books by genre", "/genres"),
+        ];
+        for (id, title, content, path) in entries {
+            let entry = self.navigation(&feed, id, title, content, path, Kind::Navigation);
+            feed.entries.push(entry);
+        }
+        let content = format!("Books added during the last {NEW_ARRIVALS_DAYS} days");
+        let entry = self.navigation(
+            &feed,
+            "new",
+            "New arrivals",
+            content,
+            "/new",
+            Kind::Acquisition,
+        );
+        feed.entries.push(entry);
+        Ok(feed)
+    }
+
+    /// Acquisition feed of the latest books grouped by the day they were added
+    pub fn new_arrivals(&self) -> Result<Feed> {
+        debug!("new_arrivals <- ");
+
+        let books = self
+            .api
+            .books_added_in_last_days_page(NEW_ARRIVALS_DAYS, self.page)?;
+        let path = "/new";
+        let mut feed = self.feed(Kind::Acquisition, "new", "New arrivals", path)?;
+        self.paginate(&mut feed, path, &books);
+
+        let series = self.serie_names(&books.items)?;
+        let genres = self.book_genres(&books.items)?;
+        for book in &books.items {
+            let entry = self.book(book, &series, &genres);
+            let href = self.url(&format!("/new/{}", book.added));
+            feed.push_grouped(book.added.clone(), Some(href), entry);
+        }
+        Ok(feed)
+    }
+
+    /// Acquisition feed of the books added on the day
+    pub fn new_arrivals_of(&self, date: Date) -> Result<Feed> {
+        debug!("new_arrivals_of <- {date}");
+
+        let books = self.api.books_added_between_page(date, date, self.page)?;
+        self.acquisition(
+            format!("new:{date}"),
+            format!("New arrivals: {date}"),
+            &format!("/new/{date}"),
+            books,
+        )
+    }
+
+    /// Navigation feed of the author last names by prefix
+    pub fn authors(&self, prefix: &String) -> Result<Feed> {
+        debug!("authors <- {prefix}");
+
+        let path = format!("/authors/{}", encode(prefix));
+        let id = format!("authors:{}", encode(prefix));
+        let mut feed = self.feed(Kind::Navigation, id, format!("Authors: {prefix}…"), &path)?;
+        let found = self.api.search_authors_by_prefix(prefix)?;
+        self.prefixes(&mut feed, "authors", ("last_name", Kind::Navigation), found);
+        Ok(feed)
+    }
+
+    /// Navigation feed of the authors by exact last name
+    pub fn authors_by_last_name(&self, name: &String) -> Result<Feed> {
+        debug!("authors_by_last_name <- {name}");
+
+        let path = format!("/last_name/{}", encode(name));
+        let id = format!("last_name:{}", encode(name));
+        let mut feed = self.feed(Kind::Navigation, id, name.clone(), &path)?;
+        let authors = self.api.authors_by_last_name_page(name, self.page)?;
+        self.paginate(&mut feed, &path, &authors);
+        for author in authors.items {
+            let entry = self.author_entry(&feed, &author);
+            feed.entries.push(entry);
+        }
+        Ok(feed)
+    }
+
+    /// Acquisition feed of the author books grouped by series
+    pub fn author(&self, fid: u32, mid: u32, lid: u32) -> Result<Feed> {
+        debug!("author <- {fid}, {mid}, {lid}");
+
+        let author = self
+            .api
+            .author_by_ids(fid, mid, lid)?
+            .ok_or_else(|| Error::NotFound(format!("Author {fid}/{mid}/{lid}")))?;
+        let books = self
+            .api
+            .books_by_author_ids_page(fid, mid, lid, self.page)?;
+        let path = format!("/author/{fid}/{mid}/{lid}");
+        let id = format!("author:{fid}:{mid}:{lid}");
+        let mut feed = self.feed(Kind::Acquisition, id, author.to_string(), &path)?;
+        self.paginate(&mut feed, &path, &books);
+
+        let series = self.serie_names(&books.items)?;
+        let genres = self.book_genres(&books.items)?;
+        for book in &books.items {
+            let entry = self.book(book, &series, &genres);
+            let (title, href) = match &entry.serie {
+                Some(serie) => (serie.name.clone(), Some(serie.href.clone())),
+                None => (String::from("Without serie"), None),
+            };
+            feed.push_grouped(title, href, entry);
+        }
+        Ok(feed)
+    }
+
+    /// Navigation feed of the serie names by prefix
+    pub fn series(&self, prefix: &String) -> Result<Feed> {
+        debug!("series <- {prefix}");
+
+        let path = format!("/series/{}", encode(prefix));
+        let id = format!("series:{}", encode(prefix));
+        let mut feed = self.feed(Kind::Navigation, id, format!("Series: {prefix}…"), &path)?;
+        let found = self.api.search_series_by_prefix(prefix)?;
+        self.prefixes(&mut feed, "series", ("serie_name", Kind::Navigation), found);
+        Ok(feed)
+    }
+
+    /// Navigation feed of the series by exact name
+    pub fn series_by_name(&self, name: &String) -> Result<Feed> {
+        debug!("series_by_name <- {name}");
+
+        let path = format!("/serie_name/{}", encode(name));
+        let id = format!("serie_name:{}", encode(name));
+        let mut feed = self.feed(Kind::Navigation, id, name.clone(), &path)?;
+        let series = self.api.series_by_serie_name_page(name, self.page)?;
+        self.paginate(&mut feed, &path, &series);
+        for serie in series.items {
+            let entry = self.navigation(
+                &feed,
+                format!("serie:{}", serie.id),
+                format!("{} [{}]", serie.name, serie.author),
+                format!("{} books", serie.count),
+                &format!("/serie/{}", serie.id),
+                Kind::Acquisition,
+            );
+            feed.entries.push(entry);
+        }
+        Ok(feed)
+    }
+
+    /// Acquisition feed of the serie books
+    pub fn serie(&self, sid: u32) -> Result<Feed> {
+        debug!("serie <- {sid}");
+
+        let serie = self
+            .api
+            .series_by_ids(vec![sid])?
+            .into_iter()
+            .next()
+            .ok_or_else(|| Error::NotFound(format!("Serie {sid}")))?;
+        let books = self.api.books_by_serie_id_page(sid, self.page)?;
+        self.acquisition(
+            format!("serie:{sid}"),
+            serie.name,
+            &format!("/serie/{sid}"),
+            books,
+        )
+    }
+
+    /// Navigation feed of the book titles by prefix
+    pub fn titles(&self, prefix: &String) -> Result<Feed> {
+        debug!("titles <- {prefix}");
+
+        let path = format!("/titles/{}", encode(prefix));
+        let id = format!("titles:{}", encode(prefix));
+        let mut feed = self.feed(Kind::Navigation, id, format!("Titles: {prefix}…"), &path)?;
+        let found = self.api.search_books_by_prefix(prefix)?;
+        self.prefixes(&mut feed, "titles", ("title", Kind::Acquisition), found);
+        Ok(feed)
+    }
+
+    /// Acquisition feed of the books by exact title
+    pub fn title(&self, name: &String) -> Result<Feed> {
+        debug!("title <- {name}");
+
+        let books = self.api.books_by_book_title_page(name, self.page)?;
+        let path = format!("/title/{}", encode(name));
+        self.acquisition(
+            format!("title:{}", encode(name)),
+            name.clone(),
+            &path,
+            books,
+        )
+    }
+
+    /// Navigation feed of the meta genres
+    pub fn meta_genres(&self) -> Result<Feed> {
+        debug!("meta_genres <- ");
+
+        let mut feed = self.feed(Kind::Navigation, "genres", "Genres", "/genres")?;
+        for meta in self.api.meta_genres()? {
+            let path = format!("/genres/{}", encode(&meta));
+            let id = format!("genres:{}", encode(&meta));
+            let entry = self.navigation(&feed, id, meta.clone(), meta, &path, Kind::Navigation);
+            feed.entries.push(entry);
+        }
+        Ok(feed)
+    }
+
+    /// Navigation feed of the genres of the meta genre
+    pub fn genres(&self, meta: &String) -> Result<Feed> {
+        debug!("genres <- {meta}");
+
+        let path = format!("/genres/{}", encode(meta));
+        let id = format!("genres:{}", encode(meta));
+        let mut feed = self.feed(Kind::Navigation, id, meta.clone(), &path)?;
+        for genre in self.api.genres_by_meta(meta)? {
+            let path = format!("/genre/{}", genre.id);
+            let id = format!("genre:{}", genre.id);
+            let entry = self.navigation(
+                &feed,
+                id,
+                genre.value.clone(),
+                genre.value,
+                &path,
+                Kind::Navigation,
+            );
+            feed.entries.push(entry);
+        }
+        Ok(feed)
+    }
+
+    /// Navigation feed of the genre: authors, series and books
+    pub fn genre(&self, gid: u32) -> Result<Feed> {
+        debug!("genre <- {gid}");
+
+        let genre = self
+            .api
+            .genre_by_id(gid)?
+            .ok_or_else(|| Error::NotFound(format!("Genre {gid}")))?;
+        let path = format!("/genre/{gid}");
+        let mut feed = self.feed(Kind::Navigation, format!("genre:{gid}"), genre.value, &path)?;
+        let entries = [
+            ("authors", "Authors", Kind::Navigation),
+            ("series", "Series", Kind::Navigation),
+            ("books", "Books", Kind::Acquisition),
+        ];
+        for (name, title, kind) in entries {
+            let id = format!("genre:{gid}:{name}");
+            let path = format!("/genre/{gid}/{name}");
+            let entry = self.navigation(&feed, id, title, title, &path, kind);
+            feed.entries.push(entry);
+        }
+        Ok(feed)
+    }
+
+    /// Navigation feed of the genre authors
+    pub fn genre_authors(&self, gid: u32) -> Result<Feed> {
+        debug!("genre_authors <- {gid}");
+
+        let path = format!("/genre/{gid}/authors");
+        let mut feed = self.feed(
+            Kind::Navigation,
+            format!("genre:{gid}:authors"),
+            "Authors",
+            &path,
+        )?;
+        let authors = self.api.authors_by_genre_id_page(gid, self.page)?;
+        self.paginate(&mut feed, &path, &authors);
+        for author in authors.items {
+            let entry = self.author_entry(&feed, &author);
+            feed.entries.push(entry);
+        }
+        Ok(feed)
+    }
+
+    /// Navigation feed of the genre series
+    pub fn genre_series(&self, gid: u32) -> Result<Feed> {
+        debug!("genre_series <- {gid}");
+
+        let path = format!("/genre/{gid}/series");
+        let mut feed = self.feed(
+            Kind::Navigation,
+            format!("genre:{gid}:series"),
+            "Series",
+            &path,
+        )?;
+        let series = self.api.series_by_genre_id_page(gid, self.page)?;
+        self.paginate(&mut feed, &path, &series);
+        for serie in series.items {
+            let entry = self.navigation(
+                &feed,
+                format!("serie:{}", serie.id),
+                format!("{} [{}]", serie.name, serie.author),
+                format!("{} books", serie.count),
+                &format!("/serie/{}", serie.id),
+                Kind::Acquisition,
+            );
+            feed.entries.push(entry);
+        }
+        Ok(feed)
+    }
+
+    /// Acquisition feed of the genre books
+    pub fn genre_books(&self, gid: u32) -> Result<Feed> {
+        debug!("genre_books <- {gid}");
+
+        let books = self
+            .api
+            .books_by_genre_id_and_date_page(gid, String::from("%"), self.page)?;
+        let path = format!("/genre/{gid}/books");
+        self.acquisition(format!("genre:{gid}:books"), "Books", &path, books)
+    }
+}
+
+/// Converts the date of the book to the Atom timestamp
+pub fn timestamp(date: &str) -> String {
+    if date.is_empty() || date.contains('T') {
+        date.to_owned()
+    } else {
+        format!("{date}T00:00:00Z")
+    }
+}
+
+/// Escapes XML special characters
+pub fn escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for ch in text.chars() {
+        match ch {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&apos;"),
+            _ => out.push(ch),
+        }
+    }
+    out
+}
+
+/// Percent-encodes the URL path segment
+pub fn encode(segment: &str) -> String {
+    let mut out = String::with_capacity(segment.len());
+    for byte in segment.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                out.push(byte as char)
+            }
+            _ => out.push_str(&format!("%{byte:02X}")),
+        }
+    }
+    out
+}
+
+/// Decodes the percent-encoded URL path segment
+pub fn decode(segment: &str) -> String {
+    let bytes = segment.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = bytes
+            .get(i + 1..i + 3)
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match (bytes[i], hex) {
+            (b'%', Some(byte)) => {
+                out.push(byte);
+                i += 3;
+            }
+            (b'+', _) => {
+                out.push(b' ');
+                i += 1;
+            }
+            (byte, _) => {
+                out.push(byte);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn escape_text() {
+        assert_eq!(
+            "Tom &amp; Jerry &lt;&quot;&apos;&gt;",
+            escape("Tom & Jerry <\"'>")
+        );
+    }
+
+    #[test]
+    fn encode_segment() {
+        assert_eq!("A-z_0.9~", encode("A-z_0.9~"));
+        assert_eq!("%D0%90%20b%2F", encode("А b/"));
+    }
+
+    #[test]
+    fn decode_segment() {
+        assert_eq!("А b/", decode("%D0%90%20b%2F"));
+        assert_eq!("a b", decode("a+b"));
+        assert_eq!("100%", decode("100%"));
+        assert_eq!("%zz", decode("%zz"));
+    }
+
+    #[test]
+    fn timestamps() {
+        assert_eq!("2024-06-01T00:00:00Z", timestamp("2024-06-01"));
+        assert_eq!("2024-06-01T10:00:00Z", timestamp("2024-06-01T10:00:00Z"));
+    }
+
+    #[test]
+    fn link() {
+        assert_eq!(
+            r#"<link rel="subsection" href="/opds/serie/1" type="application/atom+xml;profile=opds-catalog;kind=acquisition" title="A &amp; B"/>"#,
+            format!(
+                "{}",
+                Link::new("subsection", "/opds/serie/1", ACQUISITION).with_title("A & B")
+            )
+        );
+    }
+
+    #[test]
+    fn feed() {
+        let feed = Feed {
+            id: String::from("urn:opds_api:root"),
+            title: String::from("Catalog"),
+            updated: String::from("2024-06-30T00:00:00Z"),
+            kind: Kind::Navigation,
+            links: vec![Link::new("self", "/opds", NAVIGATION)],
+            entries: vec![Entry {
+                id: String::from("urn:opds_api:genres"),
+                title: String::from("Genres"),
+                updated: String::from("2024-06-30T00:00:00Z"),
+                authors: vec![Person {
+                    name: String::from("A"),
+                    uri: None,
+                }],
+                serie: None,
+                language: None,
+                categories: vec![],
+                summary: None,
+                content: Some(String::from("Books by genre")),
+                links: vec![Link::new("subsection", "/opds/genres", NAVIGATION)],
+            }],
+            groups: vec![],
+        };
+        assert_eq!(
+            concat!(
+                r#"<?xml version="1.0" encoding="UTF-8"?>"#,
+                r#"<feed xmlns="http://www.w3.org/2005/Atom" xmlns:dc="http://purl.org/dc/terms/" xmlns:opds="http://opds-spec.org/2010/catalog">"#,
+                r#"<id>urn:opds_api:root</id><title>Catalog</title><updated>2024-06-30T00:00:00Z</updated>"#,
+                r#"<author><name>opds_api</name></author>"#,
+                r#"<link rel="self" href="/opds" type="application/atom+xml;profile=opds-catalog;kind=navigation"/>"#,
+                r#"<entry><id>urn:opds_api:genres</id><title>Genres</title><updated>2024-06-30T00:00:00Z</updated>"#,
+                r#"<author><name>A</name></author>"#,
+                r#"<content type="text">Books by genre</content>"#,
+                r#"<link rel="subsection" href="/opds/genres" type="application/atom+xml;profile=opds-catalog;kind=navigation"/>"#,
+                r#"</entry></feed>"#
+            ),
+            format!("{feed}")
+        );
+    }
+}
@@ -0,0 +1,339 @@
+use std::fmt;
+
+use crate::opds::{
+    Entry, Feed, Group, Kind, Link, ACQUISITION, NAVIGATION, REL_IMAGE, REL_THUMBNAIL,
+};
+
+/// Mime type of the OPDS 2.0 feed
+pub const MIME: &str = "application/opds+json";
+
+const BOOK: &str = "http://schema.org/Book";
+
+/// Minimal JSON document model
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Json {
+    Number(u32),
+    String(String),
+    Array(Vec<Json>),
+    Object(Vec<(&'static str, Json)>),
+}
+impl Json {
+    fn string<S: AsRef<str>>(value: S) -> Self {
+        Self::String(value.as_ref().to_owned())
+    }
+}
+impl fmt::Display for Json {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Number(value) => write!(fmt, "{value}"),
+            Self::String(value) => write!(fmt, "\"{}\"", escape(value)),
+            Self::Array(values) => {
+                fmt.write_str("[")?;
+                for (n, value) in values.iter().enumerate() {
+                    if n > 0 {
+                        fmt.write_str(",")?;
+                    }
+                    write!(fmt, "{value}")?;
+                }
+                fmt.write_str("]")
+            }
+            Self::Object(fields) => {
+                fmt.write_str("{")?;
+                for (n, (name, value)) in fields.iter().enumerate() {
+                    if n > 0 {
+                        fmt.write_str(",")?;
+                    }
+                    write!(fmt, "\"{name}\":{value}")?;
+                }
+                fmt.write_str("}")
+            }
+        }
+    }
+}
+
+/// Renders the feed as OPDS 2.0 JSON document
+///
+/// Navigation entries go to `navigation`, acquisition entries go to
+/// `publications` and feed groups go to `groups`
+pub fn render(feed: &Feed) -> String {
+    let mut fields = vec![
+        (
+            "metadata",
+            Json::Object(vec![
+                ("identifier", Json::string(&feed.id)),
+                ("title", Json::string(&feed.title)),
+                ("modified", Json::string(&feed.updated)),
+            ]),
+        ),
+        ("links", links(&feed.links)),
+    ];
+    fields.append(&mut collections(&feed.entries));
+    if !feed.groups.is_empty() {
+        let groups = feed.groups.iter().map(|g| group(g, feed.kind)).collect();
+        fields.push(("groups", Json::Array(groups)));
+    }
+    Json::Object(fields).to_string()
+}
+
+fn group(group: &Group, kind: Kind) -> Json {
+    let mut fields = vec![(
+        "metadata",
+        Json::Object(vec![("title", Json::string(&group.title))]),
+    )];
+    if let Some(href) = &group.href {
+        let link = Link::new("self", href.as_str(), kind.mime());
+        fields.push(("links", links(&[link])));
+    }
+    fields.append(&mut collections(&group.entries));
+    Json::Object(fields)
+}
+
+fn collections(entries: &[Entry]) -> Vec<(&'static str, Json)> {
+    let (publications, navigation): (Vec<&Entry>, Vec<&Entry>) =
+        entries.iter().partition(|entry| entry.is_publication());
+
+    let mut fields = Vec::new();
+    if !navigation.is_empty() {
+        let navigation = navigation.into_iter().filter_map(navigation_link).collect();
+        fields.push(("navigation", Json::Array(navigation)));
+    }
+    if !publications.is_empty() {
+        let publications = publications.into_iter().map(publication).collect();
+        fields.push(("publications", Json::Array(publications)));
+    }
+    fields
+}
+
+fn navigation_link(entry: &Entry) -> Option<Json> {
+    entry.links.first().map(|link| {
+        Json::Object(vec![
+            ("href", Json::string(&link.href)),
+            ("title", Json::string(&entry.title)),
+            ("type", Json::string(mime(&link.mime))),
+            ("rel", Json::string(&link.rel)),
+        ])
+    })
+}
+
+fn publication(entry: &Entry) -> Json {
+    let mut metadata = vec![
+        ("@type", Json::string(BOOK)),
+        ("identifier", Json::string(&entry.id)),
+        ("title", Json::string(&entry.title)),
+        ("modified", Json::string(&entry.updated)),
+    ];
+    if !entry.authors.is_empty() {
+        let authors = entry
+            .authors
+            .iter()
+            .map(|author| {
+                let mut fields = vec![("name", Json::string(&author.name))];
+                if let Some(uri) = &author.uri {
+                    fields.push(("links", links(&[Link::new("", uri.as_str(), ACQUISITION)])));
+                }
+                Json::Object(fields)
+            })
+            .collect();
+        metadata.push(("author", Json::Array(authors)));
+    }
+    if let Some(language) = &entry.language {
+        metadata.push(("language", Json::string(language)));
+    }
+    if !entry.categories.is_empty() {
+        let subjects = entry
+            .categories
+            .iter()
+            .map(|category| {
+                Json::Object(vec![
+                    ("name", Json::string(&category.label)),
+                    ("code", Json::string(&category.term)),
+                    (
+                        "links",
+                        links(&[Link::new("", category.href.as_str(), NAVIGATION)]),
+                    ),
+                ])
+            })
+            .collect();
+        metadata.push(("subject", Json::Array(subjects)));
+    }
+    if let Some(description) = entry.summary.as_ref().or(entry.content.as_ref()) {
+        metadata.push(("description", Json::string(description)));
+    }
+    if let Some(serie) = &entry.serie {
+        let mut fields = vec![("name", Json::string(&serie.name))];
+        if let Some(position) = serie.position {
+            fields.push(("position", Json::Number(position)));
+        }
+        fields.push((
+            "links",
+            links(&[Link::new("", serie.href.as_str(), ACQUISITION)]),
+        ));
+        metadata.push((
+            "belongsTo",
+            Json::Object(vec![("series", Json::Array(vec![Json::Object(fields)]))]),
+        ));
+    }
+
+    let (images, other): (Vec<Link>, Vec<Link>) = entry
+        .links
+        .iter()
+        .cloned()
+        .partition(|link| link.rel == REL_IMAGE || link.rel == REL_THUMBNAIL);
+    let mut fields = vec![
+        ("metadata", Json::Object(metadata)),
+        ("links", links(&other)),
+    ];
+    if !images.is_empty() {
+        fields.push(("images", links(&images)));
+    }
+    Json::Object(fields)
+}
+
+fn links(links: &[Link]) -> Json {
+    let links = links
+        .iter()
+        .map(|link| {
+            let mut fields = Vec::new();
+            if !link.rel.is_empty() {
+                fields.push(("rel", Json::string(&link.rel)));
+            }
+            fields.push(("href", Json::string(&link.href)));
+            fields.push(("type", Json::string(mime(&link.mime))));
+            if let Some(title) = &link.title {
+                fields.push(("title", Json::string(title)));
+            }
+            Json::Object(fields)
+        })
+        .collect();
+    Json::Array(links)
+}
+
+/// Replaces Atom feed mime types with the OPDS 2.0 one
+fn mime(mime: &str) -> &str {
+    match mime {
+        NAVIGATION | ACQUISITION => MIME,
+        other => other,
+    }
+}
+
+/// Escapes JSON string special characters
+fn escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for ch in text.chars() {
+        match ch {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            ch if (ch as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", ch as u32)),
+            ch => out.push(ch),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::opds::{Category, Member, Person};
+
+    #[test]
+    fn escape_text() {
+        assert_eq!(r#"a\"b\\c\n\u0001"#, escape("a\"b\\c\n\u{1}"));
+    }
+
+    #[test]
+    fn navigation() {
+        let feed = Feed {
+            id: String::from("urn:opds_api:root"),
+            title: String::from("Catalog"),
+            updated: String::from("2024-06-30T00:00:00Z"),
+            kind: Kind::Navigation,
+            links: vec![Link::new("self", "/opds2", NAVIGATION)],
+            entries: vec![Entry {
+                id: String::from("urn:opds_api:genres"),
+                title: String::from("Genres"),
+                updated: String::from("2024-06-30T00:00:00Z"),
+                authors: vec![],
+                serie: None,
+                language: None,
+                categories: vec![],
+                summary: None,
+                content: Some(String::from("Books by genre")),
+                links: vec![Link::new("subsection", "/opds2/genres", NAVIGATION)],
+            }],
+            groups: vec![],
+        };
+        assert_eq!(
+            concat!(
+                r#"{"metadata":{"identifier":"urn:opds_api:root","title":"Catalog","modified":"2024-06-30T00:00:00Z"},"#,
+                r#""links":[{"rel":"self","href":"/opds2","type":"application/opds+json"}],"#,
+                r#""navigation":[{"href":"/opds2/genres","title":"Genres","type":"application/opds+json","rel":"subsection"}]}"#
+            ),
+            render(&feed)
+        );
+    }
+
+    #[test]
+    fn publications() {
+        let feed = Feed {
+            id: String::from("urn:opds_api:serie:1"),
+            title: String::from("S"),
+            updated: String::from("2024-06-30T00:00:00Z"),
+            kind: Kind::Acquisition,
+            links: vec![],
+            entries: vec![],
+            groups: vec![Group {
+                title: String::from("S"),
+                href: Some(String::from("/opds2/serie/1")),
+                entries: vec![Entry {
+                    id: String::from("urn:opds_api:book:1"),
+                    title: String::from("T"),
+                    updated: String::from("2024-06-01T00:00:00Z"),
+                    authors: vec![Person {
+                        name: String::from("A"),
+                        uri: Some(String::from("/opds2/author/1/2/3")),
+                    }],
+                    serie: Some(Member {
+                        name: String::from("S"),
+                        position: Some(2),
+                        href: String::from("/opds2/serie/1"),
+                    }),
+                    language: Some(String::from("uk")),
+                    categories: vec![Category {
+                        term: String::from("sf_fantasy"),
+                        label: String::from("Фэнтези"),
+                        href: String::from("/opds2/genre/7"),
+                    }],
+                    summary: None,
+                    content: None,
+                    links: vec![
+                        Link::new(
+                            "http://opds-spec.org/acquisition/open-access",
+                            "/opds2/book/1/fb2",
+                            "application/x-fictionbook+xml",
+                        ),
+                        Link::new(REL_IMAGE, "/opds2/book/1/cover", "image/jpeg"),
+                    ],
+                }],
+            }],
+        };
+        assert_eq!(
+            concat!(
+                r#"{"metadata":{"identifier":"urn:opds_api:serie:1","title":"S","modified":"2024-06-30T00:00:00Z"},"#,
+                r#""links":[],"#,
+                r#""groups":[{"metadata":{"title":"S"},"#,
+                r#""links":[{"rel":"self","href":"/opds2/serie/1","type":"application/opds+json"}],"#,
+                r#""publications":[{"metadata":{"@type":"http://schema.org/Book","identifier":"urn:opds_api:book:1","title":"T","modified":"2024-06-01T00:00:00Z","#,
+                r#""author":[{"name":"A","links":[{"href":"/opds2/author/1/2/3","type":"application/opds+json"}]}],"#,
+                r#""language":"uk","#,
+                r#""subject":[{"name":"Фэнтези","code":"sf_fantasy","links":[{"href":"/opds2/genre/7","type":"application/opds+json"}]}],"#,
+                r#""belongsTo":{"series":[{"name":"S","position":2,"links":[{"href":"/opds2/serie/1","type":"application/opds+json"}]}]}},"#,
+                r#""links":[{"rel":"http://opds-spec.org/acquisition/open-access","href":"/opds2/book/1/fb2","type":"application/x-fictionbook+xml"}],"#,
+                r#""images":[{"rel":"http://opds-spec.org/image","href":"/opds2/book/1/cover","type":"image/jpeg"}]}]}]}"#
+            ),
+            render(&feed)
+        );
+    }
+}
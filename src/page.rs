@@ -0,0 +1,95 @@
+/// Default number of items on the page
+pub const PAGE_SIZE: u32 = 50;
+
+/// Requested slice of the list: `limit` items starting from `offset`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PageRequest {
+    pub offset: u32,
+    pub limit: u32,
+}
+impl PageRequest {
+    pub fn new(offset: u32, limit: u32) -> Self {
+        Self { offset, limit }
+    }
+
+    /// Returns request of the page by its zero based number
+    pub fn number(number: u32, limit: u32) -> Self {
+        Self::new(number.saturating_mul(limit), limit)
+    }
+
+    /// Returns zero based number of the page
+    pub fn index(&self) -> u32 {
+        self.offset.checked_div(self.limit).unwrap_or_default()
+    }
+}
+impl Default for PageRequest {
+    fn default() -> Self {
+        Self::new(0, PAGE_SIZE)
+    }
+}
+
+/// Slice of the list with the total count and the requests of the adjacent pages
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    pub total: u32,
+    pub next: Option<PageRequest>,
+    pub prev: Option<PageRequest>,
+}
+impl<T> Page<T> {
+    /// Creates the page of items fetched by the request
+    pub fn new(items: Vec<T>, total: u32, request: PageRequest) -> Self {
+        let end = request.offset.saturating_add(items.len() as u32);
+        let next = if end < total && !items.is_empty() {
+            Some(PageRequest::new(end, request.limit))
+        } else {
+            None
+        };
+        let prev = if request.offset > 0 {
+            let offset = request.offset.saturating_sub(request.limit);
+            Some(PageRequest::new(offset, request.limit))
+        } else {
+            None
+        };
+        Self {
+            items,
+            total,
+            next,
+            prev,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn request() {
+        assert_eq!(PageRequest::number(2, 10), PageRequest::new(20, 10));
+        assert_eq!(PageRequest::new(25, 10).index(), 2);
+        assert_eq!(PageRequest::new(25, 0).index(), 0);
+    }
+
+    #[test]
+    fn new() {
+        let first = Page::new((1..=10).collect::<Vec<u32>>(), 25, PageRequest::new(0, 10));
+        assert_eq!(first.total, 25);
+        assert_eq!(first.prev, None);
+        assert_eq!(first.next, Some(PageRequest::new(10, 10)));
+
+        let last = Page::new(
+            (21..=25).collect::<Vec<u32>>(),
+            25,
+            PageRequest::new(20, 10),
+        );
+        assert_eq!(last.prev, Some(PageRequest::new(10, 10)));
+        assert_eq!(last.next, None);
+
+        let beyond = Page::<u32>::new(Vec::new(), 25, PageRequest::new(40, 10));
+        assert_eq!(beyond.next, None);
+        assert_eq!(beyond.prev, Some(PageRequest::new(30, 10)));
+    }
+}
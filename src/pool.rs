@@ -0,0 +1,150 @@
+use log::{debug, error};
+use rusqlite::{Connection, OpenFlags};
+
+use std::{
+    ops::Deref,
+    path::Path,
+    sync::{Condvar, Mutex, PoisonError},
+};
+
+use crate::{Error, OpdsApi, Result};
+
+/// Fixed size pool of read-only OpdsApi connections
+///
+/// The pool is `Sync`, so it can be shared by the request handlers
+/// and every handler gets its own connection for the time of the call.
+/// The language filter set by the handler is reset to the pool default
+/// when the connection is released.
+#[derive(Debug)]
+pub struct OpdsPool {
+    idle: Mutex<Vec<OpdsApi>>,
+    released: Condvar,
+    size: usize,
+    /// Ids of the languages accepted by default
+    languages: Option<Vec<u32>>,
+}
+impl OpdsPool {
+    /// Opens `size` read-only connections to the database path or URI
+    pub fn open(database: &str, size: usize) -> Result<Self> {
+        debug!("open <- {database}, {size}");
+        Self::open_with_flags(Path::new(database), size, OpenFlags::SQLITE_OPEN_URI)
+    }
+
+    /// Opens `size` read-only connections to the database file
+    ///
+    /// Unlike [OpdsPool::open] the path is never parsed as URI,
+    /// so it may contain `?`, `#` and `%`
+    pub fn open_path<P: AsRef<Path>>(path: P, size: usize) -> Result<Self> {
+        debug!("open_path <- {}, {size}", path.as_ref().display());
+        Self::open_with_flags(path.as_ref(), size, OpenFlags::empty())
+    }
+
+    /// Upgrades the schema of the database file to [crate::schema::VERSION], then opens the pool
+    ///
+    /// The file is opened for writing only for the time of the migration and it is never created.
+    pub fn open_migrated<P: AsRef<Path>>(path: P, size: usize) -> Result<Self> {
+        debug!("open_migrated <- {}, {size}", path.as_ref().display());
+        let flags = OpenFlags::SQLITE_OPEN_READ_WRITE | OpenFlags::SQLITE_OPEN_NO_MUTEX;
+        let conn =
+            Connection::open_with_flags(path.as_ref(), flags).inspect_err(|e| error!("{e}"))?;
+        OpdsApi::with_functions(conn)?.migrate()?;
+        Self::open_path(path, size)
+    }
+
+    fn open_with_flags(database: &Path, size: usize, flags: OpenFlags) -> Result<Self> {
+        if size == 0 {
+            return Err(Error::InvalidArgument(String::from("empty pool")));
+        }
+        let flags = flags | OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX;
+        let idle = (0..size)
+            .map(|_| {
+                let conn =
+                    Connection::open_with_flags(database, flags).inspect_err(|e| error!("{e}"))?;
+                OpdsApi::with_functions(conn)
+            })
+            .collect::<Result<Vec<_>>>()?;
+        idle[0].check_schema()?;
+        #[cfg(feature = "validate")]
+        idle[0].validate()?;
+
+        Ok(Self {
+            idle: Mutex::new(idle),
+            released: Condvar::new(),
+            size,
+            languages: None,
+        })
+    }
+
+    /// Number of connections in the pool
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Takes the idle connection, waits until one is released if all are busy
+    pub fn get(&self) -> PooledApi<'_> {
+        let mut idle = self.idle.lock().unwrap_or_else(PoisonError::into_inner);
+        loop {
+            if let Some(api) = idle.pop() {
+                return PooledApi {
+                    pool: self,
+                    api: Some(api),
+                };
+            }
+            idle = self
+                .released
+                .wait(idle)
+                .unwrap_or_else(PoisonError::into_inner);
+        }
+    }
+
+    /// Takes the idle connection if any
+    pub fn try_get(&self) -> Option<PooledApi<'_>> {
+        let mut idle = self.idle.lock().unwrap_or_else(PoisonError::into_inner);
+        idle.pop().map(|api| PooledApi {
+            pool: self,
+            api: Some(api),
+        })
+    }
+
+    /// Restricts the lists of all connections to the books in the languages by default
+    ///
+    /// The taken connection may set its own languages until it is released,
+    /// see [OpdsApi::set_languages]
+    pub fn set_languages<S: AsRef<str>>(&mut self, codes: &[S]) -> Result<()> {
+        let idle = self.idle.get_mut().unwrap_or_else(PoisonError::into_inner);
+        idle[0].set_languages(codes)?;
+        self.languages = idle[0].language_ids();
+        for api in idle.iter() {
+            api.set_language_ids(self.languages.clone());
+        }
+        Ok(())
+    }
+
+    fn release(&self, api: OpdsApi) {
+        api.set_language_ids(self.languages.clone());
+        let mut idle = self.idle.lock().unwrap_or_else(PoisonError::into_inner);
+        idle.push(api);
+        self.released.notify_one();
+    }
+}
+
+/// Connection taken from the OpdsPool, returns to the pool on drop
+#[derive(Debug)]
+pub struct PooledApi<'a> {
+    pool: &'a OpdsPool,
+    api: Option<OpdsApi>,
+}
+impl Deref for PooledApi<'_> {
+    type Target = OpdsApi;
+
+    fn deref(&self) -> &OpdsApi {
+        self.api.as_ref().expect("connection is taken until drop")
+    }
+}
+impl Drop for PooledApi<'_> {
+    fn drop(&mut self) {
+        if let Some(api) = self.api.take() {
+            self.pool.release(api);
+        }
+    }
+}
@@ -1,153 +1,223 @@
-use lazy_static;
-use rusqlite::Row;
-use std::collections::HashMap;
-
-use crate::{Author, Book, Serie, Value};
-
-#[derive(Debug)]
-pub enum Mapper {
-    String(fn(&Row) -> rusqlite::Result<String>),
-    Value(fn(&Row) -> rusqlite::Result<Value>),
-    Author(fn(&Row) -> rusqlite::Result<Author>),
-    Serie(fn(&Row) -> rusqlite::Result<Serie>),
-    Book(fn(&Row) -> rusqlite::Result<Book>),
-    None,
+use rusqlite::{
+    types::{ToSqlOutput, Value as SqlValue},
+    Row, ToSql,
+};
+
+use std::rc::Rc;
+
+use crate::{Author, AuthorInfo, Book, Date, Found, Genre, Serie, Value};
+
+/// Query with the SQL, the parameters and the row type known at compile time
+pub trait TypedQuery {
+    type Params: QueryParams;
+    type Row: Columns;
+
+    /// Name of the query
+    const NAME: &'static str;
+    /// SQL of the query, `$N` placeholders are bound from the params in order
+    const SQL: &'static str;
+    /// Result columns the row mapper reads
+    const COLUMNS: &'static [&'static str] = <Self::Row as Columns>::COLUMNS;
+
+    /// Maps the row of the result
+    fn map(row: &Row) -> rusqlite::Result<Self::Row>;
 }
 
-#[derive(Debug, Clone, Hash, PartialEq, Eq)]
-pub enum Query {
-    AuthorByIds,
-    AuthorNextCharByPrefix,
-    AuthorsByBooksIds,
-    AuthorsByGenreId,
-    AuthorsByLastName,
-    BookById,
-    BookNextCharByPrefix,
-    BooksByAuthorIds,
-    BooksByBookTitle,
-    BooksByGenreIdAndDate,
-    BooksBySerieId,
-    GenresByMeta,
-    MetaGenres,
-    SerieNextCharByPrefix,
-    SeriesByAuthorIds,
-    SeriesByGenreId,
-    SeriesByIds,
-    SeriesBySerieName,
+/// Query which is fetched by pages
+pub trait PagedQuery: TypedQuery {
+    /// SQL counting all rows of the query
+    const COUNT_SQL: &'static str;
+    /// SQL of the query with `LIMIT` and `OFFSET` bound after the params
+    const PAGE_SQL: &'static str;
 }
-impl Query {
-    pub const VALUES: [Self; 18] = [
-        Self::AuthorNextCharByPrefix,
-        Self::SerieNextCharByPrefix,
-        Self::BookNextCharByPrefix,
-        Self::AuthorsByLastName,
-        Self::SeriesBySerieName,
-        Self::SeriesByAuthorIds,
-        Self::AuthorByIds,
-        Self::BookById,
-        Self::BooksByAuthorIds,
-        Self::BooksBySerieId,
-        Self::BooksByBookTitle,
-        Self::MetaGenres,
-        Self::GenresByMeta,
-        Self::SeriesByGenreId,
-        Self::AuthorsByGenreId,
-        Self::BooksByGenreIdAndDate,
-        Self::AuthorsByBooksIds,
-        Self::SeriesByIds,
+
+/// Result columns read by the row mapper of the type
+pub trait Columns {
+    const COLUMNS: &'static [&'static str];
+}
+
+macro_rules! columns {
+    ($($row:ty => [$($column:literal),*];)*) => {
+        $(
+            impl Columns for $row {
+                const COLUMNS: &'static [&'static str] = &[$($column),*];
+            }
+        )*
+    };
+}
+columns! {
+    String => ["value"];
+    u32 => ["count"];
+    Value => ["id", "value"];
+    (String, u32) => ["value", "count"];
+    (u32, Genre) => ["book_id", "id", "code", "name", "meta"];
+    Author => ["fid", "fname", "mid", "mname", "lid", "lname"];
+    AuthorInfo => ["fid", "fname", "mid", "mname", "lid", "lname", "books", "series"];
+    Serie => ["id", "name", "count", "fid", "fname", "mid", "mname", "lid", "lname"];
+    Book => ["id", "name", "sid", "idx", "size", "added", "lang"];
+    (u32, Author) => ["book_id", "fid", "fname", "mid", "mname", "lid", "lname"];
+    Found => [
+        "kind", "id", "name", "sid", "idx", "count", "size", "added", "lang",
+        "fid", "fname", "mid", "mname", "lid", "lname"
     ];
+}
 
-    pub fn get(&self) -> anyhow::Result<&'static str> {
-        match MAP.get(self).cloned() {
-            Some(sql) => Ok(sql),
-            None => Err(anyhow::anyhow!("SQL for {:?} is not defined", self)),
-        }
+/// Name, SQL and the read columns of the query
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct QueryInfo {
+    pub name: &'static str,
+    pub sql: &'static str,
+    pub columns: &'static [&'static str],
+}
+
+/// Parameters of the query bound in order
+pub trait QueryParams {
+    fn values(&self) -> Vec<&dyn ToSql>;
+}
+impl QueryParams for () {
+    fn values(&self) -> Vec<&dyn ToSql> {
+        Vec::new()
     }
+}
 
-    pub fn mapper(&self) -> Mapper {
-        match self {
-            Self::AuthorNextCharByPrefix => Mapper::String(map_to_string),
-            Self::SerieNextCharByPrefix => Mapper::String(map_to_string),
-            Self::BookNextCharByPrefix => Mapper::String(map_to_string),
-
-            Self::AuthorByIds => Mapper::Author(map_to_author),
-            Self::AuthorsByGenreId => Mapper::Author(map_to_author),
-            Self::AuthorsByLastName => Mapper::Author(map_to_author),
-            Self::AuthorsByBooksIds => Mapper::Author(map_to_author),
-
-            Self::SeriesByIds => Mapper::Serie(map_to_serie),
-            Self::SeriesByGenreId => Mapper::Serie(map_to_serie),
-            Self::SeriesBySerieName => Mapper::Serie(map_to_serie),
-            Self::SeriesByAuthorIds => Mapper::Serie(map_to_serie),
-
-            Self::BookById => Mapper::Book(map_to_book),
-            Self::BooksBySerieId => Mapper::Book(map_to_book),
-            Self::BooksByAuthorIds => Mapper::Book(map_to_book),
-            Self::BooksByBookTitle => Mapper::Book(map_to_book),
-            Self::BooksByGenreIdAndDate => Mapper::Book(map_to_book),
-
-            Self::MetaGenres => Mapper::String(map_to_string),
-            Self::GenresByMeta => Mapper::Value(map_to_value),
+macro_rules! query_params {
+    ($($name:ident: $index:tt),+) => {
+        impl<$($name: ToSql),+> QueryParams for ($($name,)+) {
+            fn values(&self) -> Vec<&dyn ToSql> {
+                vec![$(&self.$index),+]
+            }
         }
+    };
+}
+query_params!(A: 0);
+query_params!(A: 0, B: 1);
+query_params!(A: 0, B: 1, C: 2);
+query_params!(A: 0, B: 1, C: 2, D: 3);
+
+/// Values bound as the array of `rarray()`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Array<T>(pub Vec<T>);
+impl<T: Clone + Into<SqlValue>> ToSql for Array<T> {
+    fn to_sql(&self) -> rusqlite::Result<ToSqlOutput<'_>> {
+        let values = self.0.iter().cloned().map(Into::into).collect();
+        Ok(ToSqlOutput::Array(Rc::new(values)))
     }
 }
 
-lazy_static::lazy_static! {
-    static ref MAP: HashMap<Query, &'static str> = {
-        let mut m = HashMap::new();
-        m.insert(
-            Query::AuthorNextCharByPrefix, r#"
+/// Implements PagedQuery for the query marked as `paged`
+macro_rules! paged_query {
+    (paged $name:ident, $sql:literal) => {
+        impl PagedQuery for $name {
+            const COUNT_SQL: &'static str = concat!("SELECT count(*) FROM (", $sql, ")");
+            const PAGE_SQL: &'static str = concat!($sql, " LIMIT ? OFFSET ?");
+        }
+    };
+}
+
+/// Declares the queries with their params, row types and mappers
+macro_rules! queries {
+    ($(
+        $(#[$meta:meta])*
+        $name:ident: $params:ty => $row:ty = $mapper:ident, $($paged:ident)? $sql:literal;
+    )*) => {
+        $(
+            $(#[$meta])*
+            #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+            pub struct $name;
+            impl TypedQuery for $name {
+                type Params = $params;
+                type Row = $row;
+
+                const NAME: &'static str = stringify!($name);
+                const SQL: &'static str = $sql;
+
+                fn map(row: &Row) -> rusqlite::Result<$row> {
+                    $mapper(row)
+                }
+            }
+            $(paged_query!($paged $name, $sql);)?
+        )*
+
+        /// All queries of the OpdsApi
+        pub const QUERIES: &[QueryInfo] = &[$(QueryInfo {
+            name: stringify!($name),
+            sql: $sql,
+            columns: <$row as Columns>::COLUMNS,
+        }),*];
+    };
+}
+
+queries! {
+    /// Next chars of the last names by the length and the lowercase GLOB pattern
+    AuthorNextCharByPrefix: (u32, String) => String = map_to_string, r#"
             SELECT DISTINCT substr(value, 1, $1) AS value
-            FROM last_names WHERE LOWER(value) GLOB $2
-            ORDER BY value COLLATE opds;
-            "#
-        );
-        m.insert(
-            Query::SerieNextCharByPrefix, r#"
+            FROM last_names WHERE LOWER(value) GLOB $2 AND EXISTS (
+                SELECT 1 FROM authors_map
+                JOIN books ON books.book_id = authors_map.book_id
+                WHERE authors_map.last_name_id = last_names.id AND books.deleted = 0
+                    AND opds_lang(books.lang_id)
+            )
+            ORDER BY value COLLATE opds_ci
+            "#;
+    /// Next chars of the serie names by the length and the lowercase GLOB pattern
+    SerieNextCharByPrefix: (u32, String) => String = map_to_string, r#"
             SELECT DISTINCT substr(value, 1, $1) AS value
-            FROM series WHERE LOWER(value) GLOB $2
-            ORDER BY value COLLATE opds;
-            "#
-        );
-        m.insert(
-            Query::BookNextCharByPrefix, r#"
+            FROM series WHERE LOWER(value) GLOB $2 AND EXISTS (
+                SELECT 1 FROM series_map
+                JOIN books ON books.book_id = series_map.book_id
+                WHERE series_map.serie_id = series.id AND books.deleted = 0
+                    AND opds_lang(books.lang_id)
+            )
+            ORDER BY value COLLATE opds_ci
+            "#;
+    /// Next chars of the book titles by the length and the lowercase GLOB pattern
+    BookNextCharByPrefix: (u32, String) => String = map_to_string, r#"
             SELECT DISTINCT substr(value, 1, $1) AS value
-            FROM titles WHERE LOWER(value) GLOB $2
-            ORDER BY value COLLATE opds;
-            "#
-        );
-        m.insert(
-            Query::AuthorsByLastName, r#"
+            FROM titles WHERE LOWER(value) GLOB $2 AND EXISTS (
+                SELECT 1 FROM books WHERE books.title_id = titles.id AND books.deleted = 0
+                    AND opds_lang(books.lang_id)
+            )
+            ORDER BY value COLLATE opds_ci
+            "#;
+    /// Authors with the counts by the lowercase last name
+    AuthorsByLastName: (String,) => AuthorInfo = map_to_author_info, paged r#"
             WITH matched(lid, name) AS (
                 SELECT id, value FROM last_names WHERE LOWER(value) = $1
             )
-            SELECT DISTINCT
+            SELECT
   	            first_names.id AS fid, first_names.value AS fname,
                 middle_names.id AS mid, middle_names.value AS mname,
-			    matched.lid AS lid, matched.name AS lname
+			    matched.lid AS lid, matched.name AS lname,
+                count(DISTINCT books.book_id) AS books,
+                count(DISTINCT series_map.serie_id) AS series
 			FROM matched
 			JOIN authors_map ON authors_map.last_name_id = lid
+            JOIN books ON books.book_id = authors_map.book_id AND books.deleted = 0
+                AND opds_lang(books.lang_id)
+            LEFT JOIN series_map ON series_map.book_id = books.book_id
             JOIN middle_names ON middle_names.id = authors_map.middle_name_id
             JOIN first_names ON first_names.id = authors_map.first_name_id
-            ORDER BY lname, fname, mname COLLATE opds;
-            "#
-        );
-        m.insert(
-            Query::AuthorsByBooksIds, r#"
+            GROUP BY fid, mid, lid
+            ORDER BY lname COLLATE opds_ci, fname COLLATE opds_ci, mname COLLATE opds_ci,
+                lid, fid, mid
+            "#;
+    /// Authors of the books by the books ids
+    AuthorsByBooksIds: (Array<u32>,) => Author = map_to_author, r#"
             SELECT DISTINCT
   	            first_names.id AS fid, first_names.value AS fname,
                 middle_names.id AS mid, middle_names.value AS mname,
 			    last_names.id AS lid, last_names.value AS lname
 			FROM authors_map
+            JOIN books ON books.book_id = authors_map.book_id
+                AND books.deleted = 0 AND opds_lang(books.lang_id)
 			JOIN first_names ON first_names.id = authors_map.first_name_id
 			JOIN middle_names ON middle_names.id = authors_map.middle_name_id
 			JOIN last_names ON last_names.id = authors_map.last_name_id
 			WHERE authors_map.book_id IN rarray($1)
-            ORDER BY lname, fname, mname COLLATE opds;
-            "#
-        );
-        m.insert(
-            Query::SeriesByIds, r#"
+            ORDER BY lname COLLATE opds_ci, fname COLLATE opds_ci, mname COLLATE opds_ci
+            "#;
+    /// Series by the series ids
+    SeriesByIds: (Array<u32>,) => Serie = map_to_serie, r#"
 			SELECT
                 series_map.serie_id AS id,
                 series.value AS name,
@@ -162,12 +232,14 @@ lazy_static::lazy_static! {
 		    JOIN first_names ON first_names.id = authors_map.first_name_id
  		    JOIN middle_names ON middle_names.id = authors_map.middle_name_id
  		    JOIN last_names ON last_names.id = authors_map.last_name_id
-            WHERE series.id IN rarray($1)
+            WHERE series.id IN rarray($1) AND books.deleted = 0 AND opds_lang(books.lang_id)
             GROUP BY 1, 2, 4, 5, 6, 7, 8, 9
-		    ORDER BY name, lname, fname, mname COLLATE opds;
-        "#);
-        m.insert(
-            Query::SeriesBySerieName, r#"
+		    ORDER BY name COLLATE opds_natural,
+                lname COLLATE opds_ci, fname COLLATE opds_ci, mname COLLATE opds_ci,
+                id, lid, fid, mid
+        "#;
+    /// Series by the exact serie name
+    SeriesBySerieName: (String,) => Serie = map_to_serie, paged r#"
             SELECT
                 series.id AS id,
                 series.value AS name,
@@ -182,13 +254,15 @@ lazy_static::lazy_static! {
 		    JOIN first_names ON first_names.id = first_name_id
 		    JOIN middle_names ON middle_names.id = middle_name_id
 		    JOIN last_names ON last_names.id = last_name_id
-            WHERE series.value = $1 AND name IS NOT NULL
+            WHERE series.value = $1 AND name IS NOT NULL AND books.deleted = 0
+                AND opds_lang(books.lang_id)
             GROUP BY 1, 4, 6, 8
-		    ORDER BY name, lname, fname, mname COLLATE opds;
-            "#
-        );
-        m.insert(
-            Query::SeriesByAuthorIds, r#"
+		    ORDER BY name COLLATE opds_natural,
+                lname COLLATE opds_ci, fname COLLATE opds_ci, mname COLLATE opds_ci,
+                id, lid, fid, mid
+            "#;
+    /// Series by the author ids
+    SeriesByAuthorIds: (u32, u32, u32) => Serie = map_to_serie, paged r#"
            	SELECT
                 series.id AS id,
                 series.value AS name,
@@ -204,14 +278,18 @@ lazy_static::lazy_static! {
 		    JOIN middle_names ON middle_names.id = middle_name_id
 		    JOIN last_names ON last_names.id = last_name_id
             WHERE first_name_id = $1 AND middle_name_id = $2 AND last_name_id = $3 AND name IS NOT NULL
+                AND books.deleted = 0 AND opds_lang(books.lang_id)
             GROUP BY 1
-		    ORDER BY name, lname, fname, mname COLLATE opds;
-            "#
-        );
-        m.insert(
-            Query::SeriesByGenreId, r#"
+		    ORDER BY name COLLATE opds_natural,
+                lname COLLATE opds_ci, fname COLLATE opds_ci, mname COLLATE opds_ci,
+                id, lid, fid, mid
+            "#;
+    /// Series by the genre id
+    SeriesByGenreId: (u32,) => Serie = map_to_serie, paged r#"
            	WITH accepted(id) AS (
-                SELECT book_id FROM genres_map WHERE genre_id = $1
+                SELECT genres_map.book_id FROM genres_map
+                JOIN books ON books.book_id = genres_map.book_id
+                WHERE genre_id = $1 AND books.deleted = 0 AND opds_lang(books.lang_id)
             )
             SELECT
 			    series.id AS id,
@@ -230,183 +308,330 @@ lazy_static::lazy_static! {
 
             WHERE series.value IS NOT NULL
             GROUP BY 1, 4, 6, 8
-		    ORDER BY name, lname, fname, mname COLLATE opds;
-            "#
-        );
-        m.insert(Query::AuthorsByGenreId, r#"
-            WITH accepted(id) AS (
-                SELECT book_id FROM genres_map WHERE genre_id = $1
+		    ORDER BY name COLLATE opds_natural,
+                lname COLLATE opds_ci, fname COLLATE opds_ci, mname COLLATE opds_ci,
+                id, lid, fid, mid
+            "#;
+    /// Authors with the counts by the genre id
+    AuthorsByGenreId: (u32,) => AuthorInfo = map_to_author_info, paged r#"
+            WITH accepted(fid, mid, lid) AS (
+                SELECT DISTINCT
+                    authors_map.first_name_id, authors_map.middle_name_id, authors_map.last_name_id
+                FROM genres_map
+                JOIN books ON books.book_id = genres_map.book_id
+                JOIN authors_map ON authors_map.book_id = books.book_id
+                WHERE genre_id = $1 AND books.deleted = 0 AND opds_lang(books.lang_id)
             )
-            SELECT DISTINCT
+            SELECT
   	            first_names.id AS fid, first_names.value AS fname,
                 middle_names.id AS mid, middle_names.value AS mname,
-			    last_names.id AS lid, last_names.value AS lname
+			    last_names.id AS lid, last_names.value AS lname,
+                count(DISTINCT books.book_id) AS books,
+                count(DISTINCT series_map.serie_id) AS series
 			FROM accepted
-			JOIN authors_map ON authors_map.book_id = accepted.id
-			JOIN first_names ON first_names.id = authors_map.first_name_id
-			JOIN middle_names ON middle_names.id = authors_map.middle_name_id
-			JOIN last_names ON last_names.id = authors_map.last_name_id
-            ORDER BY lname, fname, mname COLLATE opds;
-            "#
-        );
-        m.insert(
-            Query::BooksByGenreIdAndDate, r#"
-           	WITH accepted(id) AS (
-                SELECT book_id FROM genres_map WHERE genre_id = $1
+			JOIN authors_map ON authors_map.first_name_id = accepted.fid
+                AND authors_map.middle_name_id = accepted.mid
+                AND authors_map.last_name_id = accepted.lid
+            JOIN books ON books.book_id = authors_map.book_id AND books.deleted = 0
+                AND opds_lang(books.lang_id)
+            LEFT JOIN series_map ON series_map.book_id = books.book_id
+			JOIN first_names ON first_names.id = accepted.fid
+			JOIN middle_names ON middle_names.id = accepted.mid
+			JOIN last_names ON last_names.id = accepted.lid
+            GROUP BY fid, mid, lid
+            ORDER BY lname COLLATE opds_ci, fname COLLATE opds_ci, mname COLLATE opds_ci,
+                lid, fid, mid
+            "#;
+    /// Books by the genre id and the LIKE pattern of the date
+    BooksByGenreIdAndDate: (u32, String) => Book = map_to_book, paged r#"
+            WITH accepted(id) AS (
+                SELECT DISTINCT genres_map.book_id FROM genres_map
+                JOIN books ON books.book_id = genres_map.book_id
+                WHERE genre_id = $1 AND books.deleted = 0 AND opds_lang(books.lang_id)
             )
             SELECT
                 books.book_id AS id,
-				titles.value AS name,
-                series.id AS sid,
+                titles.value AS name,
+                series_map.serie_id AS sid,
                 series_map.serie_num AS idx,
-                first_names.id AS fid, first_names.value AS fname,
-                middle_names.id AS mid, middle_names.value AS mname,
-			    last_names.id AS lid, last_names.value AS lname,
                 books.book_size AS size,
-                dates.value AS added
+                dates.value AS added,
+                langs.value AS lang
             FROM accepted
-			JOIN books ON books.book_id = accepted.id
+            JOIN books ON books.book_id = accepted.id
             JOIN titles ON titles.id = books.title_id
-            LEFT JOIN series_map ON series_map.book_id = accepted.id
-		    LEFT JOIN series ON series.id = series_map.serie_id
-			JOIN authors_map ON authors_map.book_id = accepted.id
-   		    JOIN first_names ON first_names.id = first_name_id
-		    JOIN middle_names ON middle_names.id = middle_name_id
-		    JOIN last_names ON last_names.id = last_name_id
-			JOIN dates ON  dates.id = books.date_id
-			WHERE dates.value LIKE $2
-            ORDER BY sid, idx, name, added COLLATE opds;
-            "#
-        );
-        m.insert(
-            Query::AuthorByIds,
-            r#"
+            JOIN dates ON dates.id = books.date_id
+            JOIN langs ON langs.id = books.lang_id
+            LEFT JOIN series_map ON series_map.book_id = books.book_id
+            WHERE dates.value LIKE $2
+            ORDER BY sid, idx, name COLLATE opds_natural, added, id
+            "#;
+    /// Books added between the dates inclusive, the newest first
+    BooksAddedBetween: (Date, Date) => Book = map_to_book, paged r#"
+            WITH accepted(id) AS (
+                SELECT id FROM dates WHERE value BETWEEN $1 AND $2
+            )
+            SELECT
+                books.book_id AS id,
+                titles.value AS name,
+                series_map.serie_id AS sid,
+                series_map.serie_num AS idx,
+                books.book_size AS size,
+                dates.value AS added,
+                langs.value AS lang
+            FROM accepted
+            JOIN books ON books.date_id = accepted.id
+            JOIN titles ON titles.id = books.title_id
+            JOIN dates ON dates.id = books.date_id
+            JOIN langs ON langs.id = books.lang_id
+            LEFT JOIN series_map ON series_map.book_id = books.book_id
+            WHERE books.deleted = 0 AND opds_lang(books.lang_id)
+            ORDER BY added DESC, name COLLATE opds_natural, id
+            "#;
+    /// Author by the ids
+    AuthorByIds: (u32, u32, u32) => Author = map_to_author, r#"
             SELECT
   	            first_names.id AS fid, first_names.value AS fname,
                 middle_names.id AS mid, middle_names.value AS mname,
 			    last_names.id AS lid, last_names.value AS lname
             FROM first_names, middle_names, last_names
-            WHERE first_names.id = $1 AND middle_names.id = $2 AND last_names.id = $3;
-            "#
-        );
-
-        m.insert(
-            Query::BookById,
-            r#"
-			WITH book(book_id, title_id, date_id, book_size) AS (
-                SELECT book_id, title_id, date_id, book_size FROM books WHERE book_id = $1
-            )
+            WHERE first_names.id = $1 AND middle_names.id = $2 AND last_names.id = $3
+            "#;
+    /// Book by the id
+    BookById: (u32,) => Book = map_to_book, r#"
             SELECT
-                book.book_id AS id,
-				titles.value AS name,
-                series.id AS sid,
+                books.book_id AS id,
+                titles.value AS name,
+                series_map.serie_id AS sid,
                 series_map.serie_num AS idx,
-                first_names.id AS fid, first_names.value AS fname,
-                middle_names.id AS mid, middle_names.value AS mname,
-			    last_names.id AS lid, last_names.value AS lname,
-                book.book_size AS size,
-                dates.value AS added
-            FROM book
-			JOIN authors_map ON authors_map.book_id = book.book_id
-            JOIN titles ON titles.id = book.title_id
-            JOIN dates ON dates.id = book.date_id
-            LEFT JOIN series_map ON series_map.book_id = book.book_id
-		    LEFT JOIN series ON series.id = series_map.serie_id
-   		    JOIN first_names ON first_names.id = first_name_id
-		    JOIN middle_names ON middle_names.id = middle_name_id
-		    JOIN last_names ON last_names.id = last_name_id;
-            "#
-        );
-        m.insert(
-            Query::BooksByBookTitle,
-            r#"
-			WITH matched(id, name) AS (
-                SELECT id, value FROM titles WHERE titles.value = $1
-            )
+                books.book_size AS size,
+                dates.value AS added,
+                langs.value AS lang
+            FROM books
+            JOIN titles ON titles.id = books.title_id
+            JOIN dates ON dates.id = books.date_id
+            JOIN langs ON langs.id = books.lang_id
+            LEFT JOIN series_map ON series_map.book_id = books.book_id
+            WHERE books.book_id = $1 AND books.deleted = 0 AND opds_lang(books.lang_id)
+            "#;
+    /// Books by the exact title
+    BooksByBookTitle: (String,) => Book = map_to_book, paged r#"
             SELECT
                 books.book_id AS id,
-				matched.name AS name,
-                series.id AS sid,
+                titles.value AS name,
+                series_map.serie_id AS sid,
                 series_map.serie_num AS idx,
-                first_names.id AS fid, first_names.value AS fname,
-                middle_names.id AS mid, middle_names.value AS mname,
-			    last_names.id AS lid, last_names.value AS lname,
                 books.book_size AS size,
-                dates.value AS added
-            FROM matched
-			JOIN books ON books.title_id = matched.id
+                dates.value AS added,
+                langs.value AS lang
+            FROM titles
+            JOIN books ON books.title_id = titles.id
             JOIN dates ON dates.id = books.date_id
-			JOIN authors_map ON authors_map.book_id = books.book_id
-   		    JOIN first_names ON first_names.id = first_name_id
-		    JOIN middle_names ON middle_names.id = middle_name_id
-		    JOIN last_names ON last_names.id = last_name_id
-			LEFT JOIN series_map ON series_map.book_id = books.book_id
-		    LEFT JOIN series ON series.id = series_map.serie_id;
-            "#
-        );
-        m.insert(
-            Query::BooksByAuthorIds, r#"
-            SELECT
+            JOIN langs ON langs.id = books.lang_id
+            LEFT JOIN series_map ON series_map.book_id = books.book_id
+            WHERE titles.value = $1 AND books.deleted = 0 AND opds_lang(books.lang_id)
+            ORDER BY sid, idx, added, id
+            "#;
+    /// Books by the author ids and the deleted flag
+    BooksByAuthorIds: (u32, u32, u32, bool) => Book = map_to_book, paged r#"
+            SELECT DISTINCT
                 books.book_id AS id,
-				titles.value AS name,
-                series.id AS sid,
+                titles.value AS name,
+                series_map.serie_id AS sid,
                 series_map.serie_num AS idx,
-                first_names.id AS fid, first_names.value AS fname,
-                middle_names.id AS mid, middle_names.value AS mname,
-			    last_names.id AS lid, last_names.value AS lname,
                 books.book_size AS size,
-                dates.value AS added
-            FROM authors_map
-            JOIN books ON books.book_id = authors_map.book_id
+                dates.value AS added,
+                langs.value AS lang
+            FROM authors_map AS author
+            JOIN books ON books.book_id = author.book_id
             JOIN titles ON titles.id = books.title_id
-            JOIN dates ON  books.date_id = dates.id
+            JOIN dates ON dates.id = books.date_id
+            JOIN langs ON langs.id = books.lang_id
             LEFT JOIN series_map ON series_map.book_id = books.book_id
-		    LEFT JOIN series ON series.id = series_map.serie_id
-   		    JOIN first_names ON first_names.id = first_name_id
-		    JOIN middle_names ON middle_names.id = middle_name_id
-		    JOIN last_names ON last_names.id = last_name_id
-            WHERE first_name_id = $1 AND middle_name_id = $2 AND last_name_id = $3
-            ORDER BY sid, idx, name, added COLLATE opds;
-            "#
-        );
-        m.insert(
-            Query::BooksBySerieId, r#"
+            WHERE author.first_name_id = $1 AND author.middle_name_id = $2
+                AND author.last_name_id = $3 AND books.deleted = $4
+                AND opds_lang(books.lang_id)
+            ORDER BY sid, idx, name COLLATE opds_natural, added, id
+            "#;
+    /// Books by the author ids and the serie id, the books without serie if the id is NULL
+    BooksByAuthorIdsAndSerieId: (u32, u32, u32, Option<u32>) => Book = map_to_book, paged r#"
+            SELECT DISTINCT
+                books.book_id AS id,
+                titles.value AS name,
+                series_map.serie_id AS sid,
+                series_map.serie_num AS idx,
+                books.book_size AS size,
+                dates.value AS added,
+                langs.value AS lang
+            FROM authors_map AS author
+            JOIN books ON books.book_id = author.book_id
+            JOIN titles ON titles.id = books.title_id
+            JOIN dates ON dates.id = books.date_id
+            JOIN langs ON langs.id = books.lang_id
+            LEFT JOIN series_map ON series_map.book_id = books.book_id
+            WHERE author.first_name_id = $1 AND author.middle_name_id = $2
+                AND author.last_name_id = $3 AND series_map.serie_id IS $4
+                AND books.deleted = 0 AND opds_lang(books.lang_id)
+            ORDER BY sid, idx, name COLLATE opds_natural, added, id
+            "#;
+    /// Books by the serie id and the deleted flag
+    BooksBySerieId: (u32, bool) => Book = map_to_book, paged r#"
             SELECT
                 books.book_id AS id,
-				titles.value AS name,
-                series.id AS sid,
+                titles.value AS name,
+                series_map.serie_id AS sid,
                 series_map.serie_num AS idx,
-                first_names.id AS fid, first_names.value AS fname,
-                middle_names.id AS mid, middle_names.value AS mname,
-			    last_names.id AS lid, last_names.value AS lname,
                 books.book_size AS size,
-                dates.value AS added
+                dates.value AS added,
+                langs.value AS lang
+            FROM series_map
+            JOIN books ON books.book_id = series_map.book_id
+            JOIN titles ON titles.id = books.title_id
+            JOIN dates ON dates.id = books.date_id
+            JOIN langs ON langs.id = books.lang_id
+            WHERE series_map.serie_id = $1 AND books.deleted = $2
+                AND opds_lang(books.lang_id)
+            ORDER BY idx, name COLLATE opds_natural, added, id
+            "#;
+    /// Count of the books by the author ids and the deleted flag
+    BooksCountByAuthorIds: (u32, u32, u32, bool) => u32 = map_to_count, r#"
+            SELECT count(DISTINCT books.book_id) AS count
             FROM authors_map
             JOIN books ON books.book_id = authors_map.book_id
-            JOIN titles ON titles.id = books.title_id
-            JOIN dates ON  books.date_id = dates.id
-            LEFT JOIN series_map ON series_map.book_id = books.book_id
-		    LEFT JOIN series ON series.id = series_map.serie_id
-   		    JOIN first_names ON first_names.id = first_name_id
-		    JOIN middle_names ON middle_names.id = middle_name_id
-		    JOIN last_names ON last_names.id = last_name_id
-            WHERE series.id = $1
-            ORDER BY idx, name, added COLLATE opds;
-            "#
-        );
-        m.insert(Query::MetaGenres,
-            "SELECT DISTINCT meta AS value FROM genres_def ORDER BY value COLLATE opds"
-        );
-        m.insert(Query::GenresByMeta,r#"
+            WHERE first_name_id = $1 AND middle_name_id = $2 AND last_name_id = $3
+                AND books.deleted = $4 AND opds_lang(books.lang_id)
+            "#;
+    /// Count of the books by the serie id and the deleted flag
+    BooksCountBySerieId: (u32, bool) => u32 = map_to_count, r#"
+            SELECT count(DISTINCT books.book_id) AS count
+            FROM series_map
+            JOIN books ON books.book_id = series_map.book_id
+            WHERE series_map.serie_id = $1 AND books.deleted = $2
+                AND opds_lang(books.lang_id)
+            "#;
+    /// Meta genres
+    MetaGenres: () => String = map_to_string,
+        "SELECT DISTINCT meta AS value FROM genres_def ORDER BY value COLLATE opds_ci";
+    /// Genres by the meta genre
+    GenresByMeta: (String,) => Value = map_to_value, r#"
             SELECT genres.id AS id, genre AS value
             FROM genres_def JOIN genres ON genres.value = genres_def.code
-            WHERE meta = $1 ORDER BY value COLLATE opds;
-            "#
-        );
-
-        assert_eq!(Query::VALUES.len(), m.len());
-        return m;
-    };
+            WHERE meta = $1 ORDER BY value COLLATE opds_ci
+            "#;
+    /// Genre by the id
+    GenreById: (u32,) => Value = map_to_value, r#"
+            SELECT genres.id AS id, genre AS value
+            FROM genres JOIN genres_def ON genres_def.code = genres.value
+            WHERE genres.id = $1
+            "#;
+    /// Language codes with the number of books, most used first
+    Languages: () => (String, u32) = map_to_language, r#"
+            SELECT langs.value AS value, count(books.book_id) AS count
+            FROM books JOIN langs ON langs.id = books.lang_id
+            WHERE books.deleted = 0
+            GROUP BY langs.id
+            ORDER BY count DESC, value
+            "#;
+    /// Languages by the lowercase codes
+    LanguagesByCodes: (Array<String>,) => Value = map_to_value,
+        "SELECT id, value FROM langs WHERE LOWER(value) IN rarray($1)";
+    /// Genres of the books by the books ids
+    GenresByBooksIds: (Array<u32>,) => (u32, Genre) = map_to_book_genre, r#"
+            SELECT
+                genres_map.book_id AS book_id,
+                genres.id AS id,
+                genres.value AS code,
+                coalesce(genres_def.genre, genres.value) AS name,
+                coalesce(genres_def.meta, '') AS meta
+            FROM genres_map
+            JOIN genres ON genres.id = genres_map.genre_id
+            LEFT JOIN genres_def ON genres_def.code = genres.value
+            WHERE genres_map.book_id IN rarray($1)
+            GROUP BY genres_map.book_id, genres.id
+            ORDER BY genres_map.book_id, min(genres_map.rowid)
+            "#;
+    /// Authors of the books in the order of the books by the books ids
+    AuthorsOfBooksByIds: (Array<u32>,) => (u32, Author) = map_to_book_author, r#"
+            SELECT
+                authors_map.book_id AS book_id,
+                first_names.id AS fid, first_names.value AS fname,
+                middle_names.id AS mid, middle_names.value AS mname,
+                last_names.id AS lid, last_names.value AS lname
+            FROM authors_map
+            JOIN first_names ON first_names.id = authors_map.first_name_id
+            JOIN middle_names ON middle_names.id = authors_map.middle_name_id
+            JOIN last_names ON last_names.id = authors_map.last_name_id
+            WHERE authors_map.book_id IN rarray($1)
+            ORDER BY authors_map.book_id, authors_map.rowid
+            "#;
+    /// Date of the most recently added book
+    LatestDate: () => String = map_to_string,
+        "SELECT value FROM dates ORDER BY value DESC LIMIT 1";
+    /// Full-text search by the FTS5 pattern and the limit of the rows, every serie once
+    /// with the author of its first book
+    Search: (String,) => Found = map_to_found, paged r#"
+            WITH hits(kind, id, fid, mid, lid, rank) AS (
+                SELECT kind, id, fid, mid, lid, rank FROM search_index
+                WHERE search_index MATCH $1
+            ),
+            serie_hits(rank, kind, id, name, count, author) AS (
+                SELECT hits.rank, hits.kind, series.id, series.value,
+                    count(DISTINCT books.book_id), min(authors_map.rowid)
+                FROM hits
+                JOIN series ON series.id = hits.id
+                JOIN series_map ON series_map.serie_id = series.id
+                JOIN books ON books.book_id = series_map.book_id
+                JOIN authors_map ON authors_map.book_id = books.book_id
+                WHERE hits.kind = 'serie' AND books.deleted = 0 AND opds_lang(books.lang_id)
+                GROUP BY series.id
+            )
+            SELECT
+                hits.rank AS rank, hits.kind AS kind,
+                NULL AS id, NULL AS name, NULL AS sid, NULL AS idx,
+                NULL AS count, NULL AS size, NULL AS added, NULL AS lang,
+                first_names.id AS fid, first_names.value AS fname,
+                middle_names.id AS mid, middle_names.value AS mname,
+                last_names.id AS lid, last_names.value AS lname
+            FROM hits
+            JOIN first_names ON first_names.id = hits.fid
+            JOIN middle_names ON middle_names.id = hits.mid
+            JOIN last_names ON last_names.id = hits.lid
+            WHERE hits.kind = 'author' AND EXISTS (
+                SELECT 1 FROM authors_map
+                JOIN books ON books.book_id = authors_map.book_id
+                WHERE authors_map.first_name_id = hits.fid AND authors_map.middle_name_id = hits.mid
+                    AND authors_map.last_name_id = hits.lid
+                    AND books.deleted = 0 AND opds_lang(books.lang_id)
+            )
+            UNION ALL
+            SELECT
+                serie_hits.rank, serie_hits.kind,
+                serie_hits.id, serie_hits.name, NULL, NULL,
+                serie_hits.count, NULL, NULL, NULL,
+                first_names.id, first_names.value,
+                middle_names.id, middle_names.value,
+                last_names.id, last_names.value
+            FROM serie_hits
+            JOIN authors_map ON authors_map.rowid = serie_hits.author
+            JOIN first_names ON first_names.id = authors_map.first_name_id
+            JOIN middle_names ON middle_names.id = authors_map.middle_name_id
+            JOIN last_names ON last_names.id = authors_map.last_name_id
+            UNION ALL
+            SELECT
+                hits.rank, hits.kind,
+                books.book_id, titles.value, series_map.serie_id, series_map.serie_num,
+                NULL, books.book_size, dates.value, langs.value,
+                NULL, NULL, NULL, NULL, NULL, NULL
+            FROM hits
+            JOIN books ON books.book_id = hits.id
+            JOIN titles ON titles.id = books.title_id
+            JOIN dates ON dates.id = books.date_id
+            JOIN langs ON langs.id = books.lang_id
+            LEFT JOIN series_map ON series_map.book_id = books.book_id
+            WHERE hits.kind = 'book' AND books.deleted = 0 AND opds_lang(books.lang_id)
+            ORDER BY rank, kind, name COLLATE opds_natural,
+                lname COLLATE opds_ci, fname COLLATE opds_ci, mname COLLATE opds_ci
+            "#;
 }
 
 fn map_to_string(row: &Row) -> rusqlite::Result<String> {
@@ -414,6 +639,11 @@ fn map_to_string(row: &Row) -> rusqlite::Result<String> {
     row.get(statement.column_index("value")?)
 }
 
+fn map_to_count(row: &Row) -> rusqlite::Result<u32> {
+    let statement = row.as_ref();
+    row.get(statement.column_index("count")?)
+}
+
 fn map_to_value(row: &Row) -> rusqlite::Result<Value> {
     let statement = row.as_ref();
     let id: u32 = row.get(statement.column_index("id")?)?;
@@ -440,6 +670,39 @@ fn map_to_author(row: &Row) -> rusqlite::Result<Author> {
     ))
 }
 
+fn map_to_language(row: &Row) -> rusqlite::Result<(String, u32)> {
+    let statement = row.as_ref();
+    let value: String = row.get(statement.column_index("value")?)?;
+    let count: u32 = row.get(statement.column_index("count")?)?;
+    Ok((value, count))
+}
+
+fn map_to_book_genre(row: &Row) -> rusqlite::Result<(u32, Genre)> {
+    let statement = row.as_ref();
+    let book_id: u32 = row.get(statement.column_index("book_id")?)?;
+    let id: u32 = row.get(statement.column_index("id")?)?;
+    let code: String = row.get(statement.column_index("code")?)?;
+    let name: String = row.get(statement.column_index("name")?)?;
+    let meta: String = row.get(statement.column_index("meta")?)?;
+    Ok((book_id, Genre::new(id, code, name, meta)))
+}
+
+fn map_to_book_author(row: &Row) -> rusqlite::Result<(u32, Author)> {
+    let statement = row.as_ref();
+    let book_id: u32 = row.get(statement.column_index("book_id")?)?;
+    Ok((book_id, map_to_author(row)?))
+}
+
+fn map_to_author_info(row: &Row) -> rusqlite::Result<AuthorInfo> {
+    let statement = row.as_ref();
+
+    let books: u32 = row.get(statement.column_index("books")?)?;
+    let series: u32 = row.get(statement.column_index("series")?)?;
+    let author = map_to_author(row)?;
+
+    Ok(AuthorInfo::new(author, books, series))
+}
+
 fn map_to_serie(row: &Row) -> rusqlite::Result<Serie> {
     let statement = row.as_ref();
 
@@ -451,6 +714,17 @@ fn map_to_serie(row: &Row) -> rusqlite::Result<Serie> {
     Ok(Serie::new(id, name, count, author))
 }
 
+fn map_to_found(row: &Row) -> rusqlite::Result<Found> {
+    let statement = row.as_ref();
+
+    let kind: String = row.get(statement.column_index("kind")?)?;
+    match kind.as_str() {
+        "author" => map_to_author(row).map(Found::Author),
+        "serie" => map_to_serie(row).map(Found::Serie),
+        _ => map_to_book(row).map(Found::Book),
+    }
+}
+
 fn map_to_book(row: &Row) -> rusqlite::Result<Book> {
     let statement = row.as_ref();
 
@@ -460,7 +734,37 @@ fn map_to_book(row: &Row) -> rusqlite::Result<Book> {
     let idx: Option<u32> = row.get(statement.column_index("idx")?)?;
     let size: u32 = row.get(statement.column_index("size")?)?;
     let added: String = row.get(statement.column_index("added")?)?;
-    let author = map_to_author(row)?;
+    let lang: String = row.get(statement.column_index("lang")?)?;
+
+    Ok(Book::new(id, name, sid, idx, Vec::new(), size, added).with_lang(lang))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    Ok(Book::new(id, name, sid, idx, author, size, added))
+    #[test]
+    fn queries() {
+        let mut names = QUERIES.iter().map(|query| query.name).collect::<Vec<_>>();
+        names.sort();
+        names.dedup();
+        assert_eq!(names.len(), QUERIES.len());
+
+        let search = QUERIES.iter().find(|query| query.name == Search::NAME);
+        assert_eq!(search.map(|query| query.sql), Some(Search::SQL));
+        assert_eq!(search.map(|query| query.columns), Some(Search::COLUMNS));
+    }
+
+    #[test]
+    fn params() -> rusqlite::Result<()> {
+        let conn = rusqlite::Connection::open_in_memory()?;
+        rusqlite::vtab::array::load_module(&conn)?;
+
+        let params = (Array(vec![3_u32, 1, 2]), 1_u32);
+        let mut statement = conn.prepare("SELECT value FROM rarray($1) WHERE value > $2")?;
+        let rows = statement.query_map(params.values().as_slice(), |row| row.get(0))?;
+        assert_eq!(rows.collect::<rusqlite::Result<Vec<u32>>>()?, vec![3, 2]);
+        assert!(().values().is_empty());
+        Ok(())
+    }
 }
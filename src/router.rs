@@ -0,0 +1,269 @@
+use log::{debug, error};
+
+use std::io::Write;
+
+use crate::{
+    library::Library,
+    opds::{self, Catalog, Feed, FB2, FB2_ZIP, NAVIGATION, OPENSEARCH},
+    opds2, Error, OpdsApi, Result,
+};
+
+/// Base path of the OPDS 1.2 (Atom) catalog
+pub const ATOM_BASE: &str = "/opds";
+/// Base path of the OPDS 2.0 (JSON) catalog
+pub const JSON_BASE: &str = "/opds2";
+/// Size of the box the cover thumbnail fits in
+#[cfg(feature = "thumbnail")]
+pub const THUMBNAIL_SIZE: u32 = 240;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Format {
+    Atom,
+    Json,
+}
+
+/// HTTP response produced by the Router
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Response {
+    pub status: u16,
+    pub mime: String,
+    pub body: Vec<u8>,
+    /// File name of the attachment, if any
+    pub filename: Option<String>,
+    /// Id and size of the library book streamed by `Response::write_body` instead of the body
+    pub book: Option<(u32, u64)>,
+}
+impl Response {
+    fn new<M: Into<String>>(status: u16, mime: M, body: Vec<u8>) -> Self {
+        Self {
+            status,
+            mime: mime.into(),
+            body,
+            filename: None,
+            book: None,
+        }
+    }
+
+    fn attachment<M: Into<String>>(mime: M, filename: String, body: Vec<u8>) -> Self {
+        Self {
+            filename: Some(filename),
+            ..Self::new(200, mime, body)
+        }
+    }
+
+    fn book(filename: String, id: u32, size: u64) -> Self {
+        Self {
+            book: Some((id, size)),
+            ..Self::attachment(FB2, filename, Vec::new())
+        }
+    }
+
+    /// Plain text response, e.g. of the error
+    pub fn text(status: u16, text: &str) -> Self {
+        Self::new(
+            status,
+            "text/plain; charset=utf-8",
+            text.as_bytes().to_vec(),
+        )
+    }
+
+    fn feed(feed: Feed, format: Format) -> Self {
+        match format {
+            Format::Atom => Self::new(200, feed.kind.mime(), feed.to_string().into_bytes()),
+            Format::Json => Self::new(200, opds2::MIME, opds2::render(&feed).into_bytes()),
+        }
+    }
+
+    /// Returns the length of the body or of the streamed book
+    pub fn content_length(&self) -> u64 {
+        self.book.map_or(self.body.len() as u64, |(_, size)| size)
+    }
+
+    /// Returns the reason phrase of the status code
+    pub fn reason(&self) -> &'static str {
+        match self.status {
+            200 => "OK",
+            400 => "Bad Request",
+            404 => "Not Found",
+            405 => "Method Not Allowed",
+            503 => "Service Unavailable",
+            _ => "Internal Server Error",
+        }
+    }
+
+    /// Writes the body, the book is streamed from the library archive
+    ///
+    /// The router is not needed here, so its connection may be released before
+    pub fn write_body<W>(&self, library: Option<&Library>, out: &mut W) -> Result<()>
+    where
+        W: Write,
+    {
+        match (self.book, library) {
+            (Some((id, _)), Some(library)) => {
+                library.copy_book(id, out)?;
+            }
+            (Some((id, _)), None) => {
+                return Err(Error::NotFound(format!("Library of the book {id}")));
+            }
+            (None, _) => out.write_all(&self.body)?,
+        }
+        Ok(())
+    }
+}
+
+/// Maps request paths to the catalog feeds
+///
+/// `/opds/...` paths are served as OPDS 1.2 Atom feeds and
+/// `/opds2/...` paths are served as OPDS 2.0 JSON documents
+/// and `/<base>/book/<id>/{fb2,fb2.zip,cover,thumbnail}` paths are served from the library
+pub struct Router<'a> {
+    api: &'a OpdsApi,
+    library: Option<&'a Library>,
+}
+impl<'a> Router<'a> {
+    pub fn new(api: &'a OpdsApi) -> Self {
+        Self { api, library: None }
+    }
+
+    /// Serves book downloads from the library archives
+    pub fn with_library(mut self, library: &'a Library) -> Self {
+        self.library = Some(library);
+        self
+    }
+
+    /// Handles GET request of the target (path with optional query)
+    pub fn handle(&self, target: &str) -> Response {
+        debug!("handle <- {target}");
+
+        let path = target.split(['?', '#']).next().unwrap_or_default();
+        let segments = path
+            .split('/')
+            .filter(|s| !s.is_empty())
+            .map(opds::decode)
+            .collect::<Vec<_>>();
+        let (format, base, segments) = match segments.split_first() {
+            Some((first, rest)) if first == "opds" => (Format::Atom, ATOM_BASE, rest),
+            Some((first, rest)) if first == "opds2" => (Format::Json, JSON_BASE, rest),
+            _ => return Response::text(404, "Not Found"),
+        };
+
+        let mut catalog = match Self::page(target) {
+            Ok(page) => Catalog::new(self.api, base).with_page(page),
+            Err(e) => return Self::error(target, e),
+        };
+        if let Some(library) = self.library {
+            catalog = catalog.with_library(library);
+        }
+        let response = match segments {
+            [book, id, ext] if book == "book" => self.book(id, ext),
+            [name] if name == "opensearch.xml" => {
+                let mime = match format {
+                    Format::Atom => NAVIGATION,
+                    Format::Json => opds2::MIME,
+                };
+                let body = catalog.opensearch(mime).into_bytes();
+                Ok(Some(Response::new(200, OPENSEARCH, body)))
+            }
+            _ => Self::route(&catalog, segments)
+                .map(|feed| feed.map(|feed| Response::feed(feed, format))),
+        };
+        match response {
+            Ok(Some(response)) => response,
+            Ok(None) => Response::text(404, "Not Found"),
+            Err(e) => Self::error(target, e),
+        }
+    }
+
+    /// Maps the error to the response, only the unexpected errors are logged
+    fn error(target: &str, e: Error) -> Response {
+        match e {
+            Error::InvalidArgument(_) => Response::text(400, "Bad Request"),
+            Error::NotFound(_) => Response::text(404, "Not Found"),
+            e => {
+                error!("{target}: {e}");
+                Response::text(500, "Internal Server Error")
+            }
+        }
+    }
+
+    /// Returns the zero based page number from the `page=N` query parameter
+    fn page(target: &str) -> Result<u32> {
+        let query = target
+            .split('#')
+            .next()
+            .and_then(|target| target.split_once('?'))
+            .map(|(_, query)| query)
+            .unwrap_or_default();
+        query
+            .split('&')
+            .find_map(|pair| pair.strip_prefix("page="))
+            .map_or(Ok(0), Self::number)
+    }
+
+    /// Parses the id or the page number of the path
+    fn number(text: &str) -> Result<u32> {
+        text.parse()
+            .map_err(|e| Error::InvalidArgument(format!("Number '{text}': {e}")))
+    }
+
+    fn book(&self, id: &str, ext: &str) -> Result<Option<Response>> {
+        let library = match self.library {
+            Some(library) => library,
+            None => return Ok(None),
+        };
+        let id = Self::number(id)?;
+        if self.api.book_by_id(id)?.is_none() {
+            return Ok(None);
+        }
+        let filename = Library::file_name(id);
+        let response = match ext {
+            "fb2" => Response::book(filename, id, library.book_size(id)?),
+            "fb2.zip" => Response::attachment(FB2_ZIP, filename + ".zip", library.zipped_book(id)?),
+            "cover" => return Ok(Self::image(library.book_cover(id)?)),
+            #[cfg(feature = "thumbnail")]
+            "thumbnail" => return Ok(Self::image(library.book_thumbnail(id, THUMBNAIL_SIZE)?)),
+            #[cfg(not(feature = "thumbnail"))]
+            "thumbnail" => return Ok(Self::image(library.book_cover(id)?)),
+            _ => return Ok(None),
+        };
+        Ok(Some(response))
+    }
+
+    fn image(image: Option<(String, Vec<u8>)>) -> Option<Response> {
+        image.map(|(mime, bytes)| Response::new(200, mime, bytes))
+    }
+
+    fn route(catalog: &Catalog, segments: &[String]) -> Result<Option<Feed>> {
+        let segments = segments.iter().map(|s| s.as_str()).collect::<Vec<_>>();
+        let feed = match segments.as_slice() {
+            [] => catalog.root()?,
+            ["authors"] => catalog.authors(&String::new())?,
+            ["authors", prefix] => catalog.authors(&prefix.to_string())?,
+            ["last_name", name] => catalog.authors_by_last_name(&name.to_string())?,
+            ["author", fid, mid, lid] => {
+                catalog.author(Self::number(fid)?, Self::number(mid)?, Self::number(lid)?)?
+            }
+            ["series"] => catalog.series(&String::new())?,
+            ["series", prefix] => catalog.series(&prefix.to_string())?,
+            ["serie_name", name] => catalog.series_by_name(&name.to_string())?,
+            ["serie", sid] => catalog.serie(Self::number(sid)?)?,
+            ["titles"] => catalog.titles(&String::new())?,
+            ["titles", prefix] => catalog.titles(&prefix.to_string())?,
+            ["title", name] => catalog.title(&name.to_string())?,
+            ["search", term] => catalog.search(&term.to_string())?,
+            ["new"] => catalog.new_arrivals()?,
+            ["new", date] => match date.parse() {
+                Ok(date) => catalog.new_arrivals_of(date)?,
+                Err(_) => return Ok(None),
+            },
+            ["genres"] => catalog.meta_genres()?,
+            ["genres", meta] => catalog.genres(&meta.to_string())?,
+            ["genre", gid] => catalog.genre(Self::number(gid)?)?,
+            ["genre", gid, "authors"] => catalog.genre_authors(Self::number(gid)?)?,
+            ["genre", gid, "series"] => catalog.genre_series(Self::number(gid)?)?,
+            ["genre", gid, "books"] => catalog.genre_books(Self::number(gid)?)?,
+            _ => return Ok(None),
+        };
+        Ok(Some(feed))
+    }
+}
@@ -0,0 +1,307 @@
+use log::{debug, info};
+use rusqlite::Connection;
+
+use crate::{Error, Result};
+
+/// Version of the schema the queries are written for, stored as `PRAGMA user_version`
+pub const VERSION: u32 = 5;
+
+/// Dictionaries, books and the maps between them
+const TABLES: &str = r#"
+    CREATE TABLE IF NOT EXISTS titles(
+        id      INTEGER NOT NULL PRIMARY KEY,
+        value   TEXT NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS dates(
+        id      INTEGER NOT NULL PRIMARY KEY,
+        value   TEXT NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS langs(
+        id      INTEGER NOT NULL PRIMARY KEY,
+        value   TEXT NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS series(
+        id      INTEGER NOT NULL PRIMARY KEY,
+        value   TEXT NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS genres (
+        id      INTEGER NOT NULL PRIMARY KEY,
+        value   TEXT NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS first_names (
+        id       INTEGER NOT NULL PRIMARY KEY,
+        value    TEXT NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS middle_names (
+        id       INTEGER NOT NULL PRIMARY KEY,
+        value    TEXT NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS last_names (
+        id       INTEGER NOT NULL PRIMARY KEY,
+        value    TEXT NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS authors_map (
+        book_id         INTEGER NOT NULL,
+        first_name_id   INTEGER NOT NULL,
+        middle_name_id  INTEGER NOT NULL,
+        last_name_id    INTEGER NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS books (
+        book_id     INTEGER NOT NULL,
+        title_id    INTEGER NOT NULL,
+        date_id     INTEGER NOT NULL,
+        lang_id     INTEGER NOT NULL,
+        book_size   INTEGER NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS series_map(
+        book_id    INTEGER NOT NULL,
+        serie_id   INTEGER NOT NULL,
+        serie_num  INTEGER NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS genres_map(
+        book_id    INTEGER NOT NULL,
+        genre_id   INTEGER NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS genres_def (
+        code    TEXT NOT NULL PRIMARY KEY,
+        genre   TEXT NOT NULL,
+        meta    TEXT NOT NULL
+    );
+"#;
+
+const IMPORTS: &str = r#"
+    CREATE TABLE IF NOT EXISTS imports (
+        name        TEXT NOT NULL PRIMARY KEY,
+        first_id    INTEGER NOT NULL,
+        last_id     INTEGER NOT NULL,
+        books       INTEGER NOT NULL
+    );
+"#;
+
+const SEARCH_TABLE: &str = r#"
+    CREATE VIRTUAL TABLE IF NOT EXISTS search_index USING fts5(
+        name,
+        kind UNINDEXED,
+        id UNINDEXED,
+        fid UNINDEXED,
+        mid UNINDEXED,
+        lid UNINDEXED,
+        tokenize = 'unicode61 remove_diacritics 2'
+    );
+"#;
+
+const INDEXES: &str = r#"
+    CREATE INDEX IF NOT EXISTS idx_authors_map_bid ON authors_map (book_id);
+    CREATE INDEX IF NOT EXISTS idx_authors_map_lid ON authors_map (last_name_id);
+    CREATE INDEX IF NOT EXISTS idx_books ON books (book_id, title_id, date_id);
+    CREATE INDEX IF NOT EXISTS idx_books_tid ON books (title_id);
+    CREATE INDEX IF NOT EXISTS idx_books_did ON books (date_id);
+    CREATE INDEX IF NOT EXISTS idx_genres_def ON genres_def (code, genre, meta);
+    CREATE INDEX IF NOT EXISTS idx_genres_map ON genres_map (book_id, genre_id);
+    CREATE INDEX IF NOT EXISTS idx_series_map ON series_map (book_id, serie_id);
+    CREATE INDEX IF NOT EXISTS idx_series_map_sid ON series_map (serie_id);
+    CREATE INDEX IF NOT EXISTS idx_dates ON dates (value);
+    CREATE INDEX IF NOT EXISTS idx_genres ON genres (value);
+    CREATE INDEX IF NOT EXISTS idx_last_names ON last_names (LOWER(value));
+    CREATE INDEX IF NOT EXISTS idx_series ON series (LOWER(value));
+    CREATE INDEX IF NOT EXISTS idx_titles ON titles (LOWER(value));
+"#;
+
+/// Fills the full-text index over author names, series and titles of all books
+///
+/// The importer adds the rows of the new books, authors and series itself.
+/// Rows of the deleted books stay in the index, the search query skips them.
+const SEARCH_INDEX: &str = r#"
+    DELETE FROM search_index;
+    INSERT INTO search_index(name, kind, id, fid, mid, lid)
+        SELECT DISTINCT
+            trim(first_names.value || ' ' || middle_names.value || ' ' || last_names.value),
+            'author', NULL, first_names.id, middle_names.id, last_names.id
+        FROM authors_map
+        JOIN first_names ON first_names.id = authors_map.first_name_id
+        JOIN middle_names ON middle_names.id = authors_map.middle_name_id
+        JOIN last_names ON last_names.id = authors_map.last_name_id;
+    INSERT INTO search_index(name, kind, id, fid, mid, lid)
+        SELECT DISTINCT series.value, 'serie', series.id, NULL, NULL, NULL
+        FROM series_map
+        JOIN series ON series.id = series_map.serie_id;
+    INSERT INTO search_index(name, kind, id, fid, mid, lid)
+        SELECT titles.value, 'book', books.book_id, NULL, NULL, NULL
+        FROM books
+        JOIN titles ON titles.id = books.title_id;
+"#;
+
+/// Upgrade of the schema to the version
+struct Migration {
+    version: u32,
+    name: &'static str,
+    apply: fn(&Connection) -> Result<()>,
+}
+
+/// Migrations in the version order
+///
+/// Databases created before the versioning have `user_version` 0, so every
+/// migration checks what is already there and may run on such a database
+const MIGRATIONS: [Migration; VERSION as usize] = [
+    Migration {
+        version: 1,
+        name: "dictionaries, books and maps",
+        apply: create_tables,
+    },
+    Migration {
+        version: 2,
+        name: "language and deleted flag of books",
+        apply: add_books_columns,
+    },
+    Migration {
+        version: 3,
+        name: "imported INP files",
+        apply: create_imports,
+    },
+    Migration {
+        version: 4,
+        name: "full-text search index",
+        apply: create_search_index,
+    },
+    Migration {
+        version: 5,
+        name: "indexes",
+        apply: create_indexes,
+    },
+];
+
+/// State of the database schema
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Status {
+    /// No tables, e.g. before the first import
+    Empty,
+    /// Older version which the migrations upgrade
+    Outdated(u32),
+    Current,
+    /// Version written by a newer release, it can't be migrated
+    Unknown(u32),
+}
+
+/// Returns the schema version of the database
+pub fn version(conn: &Connection) -> Result<u32> {
+    Ok(conn.query_row("PRAGMA user_version", [], |row| row.get(0))?)
+}
+
+/// Returns the state of the database schema
+pub fn status(conn: &Connection) -> Result<Status> {
+    let version = version(conn)?;
+    let status = if version > VERSION {
+        Status::Unknown(version)
+    } else if version == VERSION {
+        Status::Current
+    } else if version == 0 && !has_tables(conn)? {
+        Status::Empty
+    } else {
+        Status::Outdated(version)
+    };
+    Ok(status)
+}
+
+/// Upgrades the schema to the current version, returns the previous version
+///
+/// The caller is responsible for running it inside of a transaction.
+pub fn migrate(conn: &Connection) -> Result<u32> {
+    let current = version(conn)?;
+    debug!("migrate <- {current}");
+
+    if current > VERSION {
+        return Err(Error::UnsupportedVersion(current));
+    }
+    for migration in MIGRATIONS.iter().filter(|m| m.version > current) {
+        info!(
+            "Migrating to schema {}: {}",
+            migration.version, migration.name
+        );
+        (migration.apply)(conn)?;
+        conn.pragma_update(None, "user_version", migration.version)?;
+    }
+    Ok(current)
+}
+
+pub(crate) fn has_tables(conn: &Connection) -> Result<bool> {
+    let count: u32 = conn.query_row("SELECT count(*) FROM sqlite_master", [], |row| row.get(0))?;
+    Ok(count > 0)
+}
+
+fn has_column(conn: &Connection, table: &str, column: &str) -> Result<bool> {
+    let mut statement = conn.prepare(&format!("PRAGMA table_info({table})"))?;
+    let names = statement
+        .query_map([], |row| row.get::<_, String>("name"))?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    Ok(names.iter().any(|name| name == column))
+}
+
+fn create_tables(conn: &Connection) -> Result<()> {
+    Ok(conn.execute_batch(TABLES)?)
+}
+
+/// Books of the early layouts have no language, they get the empty one
+fn add_books_columns(conn: &Connection) -> Result<()> {
+    if !has_column(conn, "books", "lang_id")? {
+        conn.execute_batch(
+            r#"
+            INSERT OR IGNORE INTO langs(id, value) VALUES (0, '');
+            ALTER TABLE books ADD COLUMN lang_id INTEGER NOT NULL DEFAULT 0;
+            "#,
+        )?;
+    }
+    if !has_column(conn, "books", "deleted")? {
+        conn.execute_batch("ALTER TABLE books ADD COLUMN deleted INTEGER NOT NULL DEFAULT 0")?;
+    }
+    Ok(())
+}
+
+fn create_imports(conn: &Connection) -> Result<()> {
+    Ok(conn.execute_batch(IMPORTS)?)
+}
+
+fn create_search_index(conn: &Connection) -> Result<()> {
+    conn.execute_batch(SEARCH_TABLE)?;
+    Ok(conn.execute_batch(SEARCH_INDEX)?)
+}
+
+fn create_indexes(conn: &Connection) -> Result<()> {
+    Ok(conn.execute_batch(INDEXES)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn migrate_empty() -> Result<()> {
+        let conn = Connection::open_in_memory()?;
+        assert_eq!(status(&conn)?, Status::Empty);
+
+        assert_eq!(migrate(&conn)?, 0);
+        assert_eq!(status(&conn)?, Status::Current);
+        assert!(has_column(&conn, "books", "deleted")?);
+
+        assert_eq!(migrate(&conn)?, VERSION);
+        Ok(())
+    }
+
+    #[test]
+    fn migrate_unknown() -> Result<()> {
+        let conn = Connection::open_in_memory()?;
+        conn.pragma_update(None, "user_version", VERSION + 1)?;
+        assert_eq!(status(&conn)?, Status::Unknown(VERSION + 1));
+        assert!(matches!(
+            migrate(&conn),
+            Err(Error::UnsupportedVersion(version)) if version == VERSION + 1
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn migrations() {
+        for (index, migration) in MIGRATIONS.iter().enumerate() {
+            assert_eq!(migration.version as usize, index + 1);
+        }
+    }
+}
@@ -3,6 +3,7 @@ use std::fmt;
 use crate::author::Author;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Serie {
     pub id: u32,
     pub name: String,